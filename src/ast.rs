@@ -29,6 +29,9 @@ structstruck::strike! {
                  Lte,
                  Eq,
                  NotEq,
+                 Mod,
+                 PipeForward,
+                 PipeMap,
              },
              pub right: Box<Expr>,
         }),
@@ -54,6 +57,33 @@ structstruck::strike! {
             pub paren: Range<usize>,
             pub args: Vec<Expr>,
         }),
+        Get(pub struct {
+            pub object: Box<Expr>,
+            pub name: String,
+        }),
+        Set(pub struct {
+            pub object: Box<Expr>,
+            pub name: String,
+            pub value: Box<Expr>,
+        }),
+        ArrayLit(pub struct {
+            pub elements: Vec<Expr>,
+        }),
+        Index(pub struct {
+            pub object: Box<Expr>,
+            pub index: Box<Expr>,
+            pub bracket_span: Range<usize>,
+        }),
+        IndexSet(pub struct {
+            pub object: Box<Expr>,
+            pub index: Box<Expr>,
+            pub value: Box<Expr>,
+        }),
+        Ternary(pub struct {
+            pub cond: Box<Expr>,
+            pub then_branch: Box<Expr>,
+            pub else_branch: Box<Expr>,
+        }),
     }
 }
 
@@ -73,6 +103,11 @@ structstruck::strike! {
     #[strikethrough[derive(Clone, Debug, PartialEq)]]
     pub enum Stmt {
         Block(Vec<Stmt>),
+        Class(pub struct {
+            pub name: String,
+            pub superclass: Option<Reference>,
+            pub methods: Vec<Function>,
+        }),
         Expr(Expr),
         Function (pub struct {
             pub name: String,
@@ -1,26 +1,101 @@
 use std::{
     any::Any,
-    time::{SystemTime, UNIX_EPOCH},
+    cell::RefCell,
+    fmt,
+    rc::Rc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
 };
 
 use crate::ast::{self, Expr};
 use crate::{
+    env::Env,
     interpreter::{Interpreter, InterpreterError},
     value::Value,
 };
 
 type Args = Vec<Value>;
 
-pub trait Callable: std::fmt::Debug {
+/// A stable identity handed out by [`register_callable`] when a callable is
+/// created, so `equals_callable`'s default implementation can compare ids
+/// instead of downcasting and structurally comparing fields. This is what
+/// makes two distinct closures built from an identical AST (or the same
+/// closure re-declared on a second pass through its `fun` statement)
+/// compare as different, while a callable still equals itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FnId(u32);
+
+impl FnId {
+    /// Bound methods rebind a fresh value every time `this.method` is
+    /// evaluated, so they aren't entered into the registry at all; see the
+    /// `equals_callable` override on `class::BoundMethod`, which compares
+    /// the underlying method and receiver directly instead of relying on
+    /// this id.
+    pub(crate) const UNBOUND: FnId = FnId(u32::MAX);
+}
+
+/// Registers `build`'s result under a fresh [`FnId`] (the registry's
+/// current length) and keeps a clone of it in `fn_registry`, so the id
+/// assigned here always corresponds to a live entry. Returns the built
+/// value itself so the caller can wrap it (`Rc::new`, `Value::Callable`,
+/// ...) however the call site already does.
+pub(crate) fn register_callable<C>(
+    fn_registry: &mut Vec<Box<dyn Callable>>,
+    build: impl FnOnce(FnId) -> C,
+) -> C
+where
+    C: Callable + Clone,
+{
+    let id = FnId(fn_registry.len() as u32);
+    let callable = build(id);
+    fn_registry.push(Box::new(callable.clone()));
+    callable
+}
+
+/// How many arguments a [`Callable`] accepts. `Exact` covers the common
+/// case; `Range` lets a native accept a variable number of arguments, with
+/// `None` as the upper bound meaning "no maximum".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Arity {
+    Exact(u8),
+    Range(u8, Option<u8>),
+}
+
+impl Arity {
+    pub fn accepts(&self, argc: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => argc == n as usize,
+            Arity::Range(min, max) => {
+                argc >= min as usize && max.map_or(true, |max| argc <= max as usize)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Arity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Arity::Exact(n) => write!(f, "{n}"),
+            Arity::Range(min, None) => write!(f, "at least {min}"),
+            Arity::Range(min, Some(max)) => write!(f, "{min} to {max}"),
+        }
+    }
+}
+
+pub trait Callable: std::fmt::Debug + 'static {
     fn call(&self, interpreter: &mut Interpreter, args: Args) -> Result<Value, InterpreterError>;
 
-    fn arity(&self) -> u8;
+    fn arity(&self) -> Arity;
+
+    /// See [`FnId`]: the identity `equals_callable`'s default compares by.
+    fn id(&self) -> FnId;
 
     fn boxed_clone(&self) -> Box<dyn Callable>;
 
     fn as_any(&self) -> &dyn Any;
 
-    fn equals_callable(&self, other: &dyn Callable) -> bool;
+    fn equals_callable(&self, other: &dyn Callable) -> bool {
+        self.id() == other.id()
+    }
 }
 
 impl Clone for Box<dyn Callable> {
@@ -46,21 +121,31 @@ impl PartialEq for Box<dyn Callable> {
 #[derive(Clone, Debug, PartialEq)]
 pub struct Function {
     decl: ast::Function,
+    id: FnId,
+    /// The scope `fun` was declared in, so the body can see variables from
+    /// its enclosing scope(s) instead of only its own parameters — the
+    /// same idea as `class::BoundMethod::closure`, just captured at
+    /// declaration time rather than rebuilt on every lookup.
+    closure: Rc<RefCell<Env>>,
 }
 
 impl Function {
-    pub fn new(decl: ast::Function) -> Self {
-        Self { decl }
+    pub fn new(decl: ast::Function, id: FnId, closure: Rc<RefCell<Env>>) -> Self {
+        Self { decl, id, closure }
     }
 }
 
 impl Callable for Function {
     fn call(&self, interpreter: &mut Interpreter, args: Args) -> Result<Value, InterpreterError> {
-        interpreter.execute_fun_call(&self.decl.body, &self.decl.params, args)
+        interpreter.execute_fun_call(&self.decl.body, &self.decl.params, Rc::clone(&self.closure), args)
     }
 
-    fn arity(&self) -> u8 {
-        self.decl.params.len() as u8
+    fn arity(&self) -> Arity {
+        Arity::Exact(self.decl.params.len() as u8)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
     }
 
     fn boxed_clone(&self) -> Box<dyn Callable> {
@@ -70,21 +155,20 @@ impl Callable for Function {
     fn as_any(&self) -> &dyn Any {
         self
     }
-
-    fn equals_callable(&self, other: &dyn Callable) -> bool {
-        // TODO: registered functions should probably end up in a table in `Interpreter`
-        // with unique indexes, and we would then just compare those indexes
-        other
-            .as_any()
-            .downcast_ref::<Function>()
-            .map_or(false, |a| self == a)
-    }
 }
 
 // -- Built-ins --
 
 #[derive(Debug, PartialEq, Clone)]
-pub struct Clock;
+pub struct Clock {
+    id: FnId,
+}
+
+impl Clock {
+    pub fn new(id: FnId) -> Self {
+        Self { id }
+    }
+}
 
 impl Callable for Clock {
     fn call(&self, _interpreter: &mut Interpreter, _args: Args) -> Result<Value, InterpreterError> {
@@ -93,25 +177,63 @@ impl Callable for Clock {
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards");
 
-        Ok(Value::Num(since_the_epoch.as_millis() as f64 / 1000.0))
+        Ok(Value::Num(since_the_epoch.as_secs_f64()))
     }
 
-    fn arity(&self) -> u8 {
-        0
+    fn arity(&self) -> Arity {
+        Arity::Exact(0)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
     }
 
     fn boxed_clone(&self) -> Box<dyn Callable> {
-        Box::new(Self)
+        Box::new(self.clone())
     }
 
     fn as_any(&self) -> &dyn Any {
         self
     }
+}
 
-    fn equals_callable(&self, other: &dyn Callable) -> bool {
-        other
-            .as_any()
-            .downcast_ref::<Clock>()
-            .map_or(false, |a| self == a)
+/// Elapsed seconds since the interpreter started, backed by [`Instant`]
+/// rather than `Clock`'s wall-clock `SystemTime`. Unlike `SystemTime`,
+/// `Instant` is guaranteed monotonic (it never goes backwards from a clock
+/// adjustment), so `call` never panics the way `Clock` can — that's the
+/// whole point of offering this alongside `Clock` rather than instead of
+/// it, for callers doing micro-benchmarks that need both sub-millisecond
+/// resolution and a guarantee the reading won't jump backwards.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Monotonic {
+    id: FnId,
+    start: Instant,
+}
+
+impl Monotonic {
+    pub fn new(id: FnId, start: Instant) -> Self {
+        Self { id, start }
+    }
+}
+
+impl Callable for Monotonic {
+    fn call(&self, _interpreter: &mut Interpreter, _args: Args) -> Result<Value, InterpreterError> {
+        Ok(Value::Num(self.start.elapsed().as_nanos() as f64 / 1e9))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(0)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
     }
 }
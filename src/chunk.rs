@@ -0,0 +1,88 @@
+use std::ops::Range;
+
+use crate::value::Value;
+
+/// A single instruction for the stack-based VM in [`crate::vm`].
+///
+/// Jump targets (`Jump`, `JumpIfFalse`, `Loop`) are stored as signed offsets
+/// relative to the instruction *following* the jump, matching the
+/// backpatching scheme used by [`crate::compiler::Compiler`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OpCode {
+    Constant(usize),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Negate,
+    Not,
+    Equal,
+    Less,
+    Greater,
+    Jump(isize),
+    JumpIfFalse(isize),
+    Loop(isize),
+    Call(u8),
+    GetLocal(usize),
+    SetLocal(usize),
+    GetGlobal(usize),
+    SetGlobal(usize),
+    DefineGlobal(usize),
+    Return,
+    Print,
+    Pop,
+}
+
+/// A top-level (`fun`-declared, non-capturing) function compiled to its own
+/// [`Chunk`] by [`crate::compiler::Compiler`]. Stored as a `Value::VmFunction`
+/// so calling one is just pushing its `Rc` onto the stack like any other
+/// global — the VM switches to running `chunk` for the duration of the call
+/// (see `vm::Vm::run`'s call-frame stack) and switches back on `Return`.
+#[derive(Debug)]
+pub struct VmFunction {
+    pub name: String,
+    pub arity: u8,
+    pub chunk: Chunk,
+}
+
+/// A flat, linear sequence of opcodes plus the constant pool and debug
+/// spans that back them. One `Chunk` is produced per compiled function
+/// (including the implicit top-level script function).
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<OpCode>,
+    pub constants: Vec<Value>,
+    /// Parallel to `code`: the source span that produced each instruction,
+    /// used to attribute VM runtime errors back to source locations.
+    pub spans: Vec<Range<usize>>,
+    /// Number of distinct global slots the compiler assigned; the VM
+    /// preallocates its global table to this size.
+    pub global_count: usize,
+}
+
+impl Chunk {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an instruction and returns its index, so callers can
+    /// backpatch jump offsets once the target is known.
+    pub fn emit(&mut self, op: OpCode, span: Range<usize>) -> usize {
+        self.code.push(op);
+        self.spans.push(span);
+        self.code.len() - 1
+    }
+
+    pub fn add_constant(&mut self, value: Value) -> usize {
+        self.constants.push(value);
+        self.constants.len() - 1
+    }
+
+    pub fn patch_jump(&mut self, at: usize, offset: isize) {
+        match &mut self.code[at] {
+            OpCode::Jump(o) | OpCode::JumpIfFalse(o) | OpCode::Loop(o) => *o = offset,
+            other => panic!("attempted to patch a non-jump instruction: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! The runtime representation of `class` declarations (see `parser::parse_class_decl`).
+//! A `Class` is its method table plus an optional superclass; an `Instance`
+//! is just a `Class` plus a bag of fields set ad hoc by `this.field = ...`.
+//! Bound methods are produced lazily by `Interpreter::bind_method` rather
+//! than stored on the instance — the same "wrap a function with its
+//! receiver baked in" idea `callable::Function` already uses for a
+//! closure, just keyed by `this` instead.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::ast;
+use crate::callable::{Arity, Callable, FnId};
+use crate::env::Env;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::value::Value;
+
+#[derive(Debug)]
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, ast::Function>,
+    /// What a bound method's `this` scope nests onto: `globals` for a
+    /// class with no superclass, or a scope binding `super` to the
+    /// superclass `Value` otherwise. Mirrors the scope nesting
+    /// `Resolver::resolve_class_decl` assigns distances against.
+    pub closure: Rc<RefCell<Env>>,
+}
+
+impl PartialEq for Class {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Class {
+    pub fn find_method(&self, name: &str) -> Option<&ast::Function> {
+        self.methods
+            .get(name)
+            .or_else(|| self.superclass.as_ref().and_then(|s| s.find_method(name)))
+    }
+}
+
+#[derive(Debug)]
+pub struct Instance {
+    pub class: Rc<Class>,
+    pub fields: HashMap<String, Value>,
+}
+
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+}
+
+/// A method looked up off an instance (or, via `super.method()`, off an
+/// ancestor class), with its receiver already baked in. `closure` is the
+/// class's own `this`/`super` base scope, so the method body resolves
+/// non-local names the same way it would if called directly.
+#[derive(Debug, Clone)]
+pub struct BoundMethod {
+    pub method: Rc<ast::Function>,
+    pub closure: Rc<RefCell<Env>>,
+    pub this: Value,
+}
+
+impl PartialEq for BoundMethod {
+    fn eq(&self, other: &Self) -> bool {
+        self.method == other.method && self.this == other.this
+    }
+}
+
+impl Callable for BoundMethod {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let env = Env::child(&self.closure);
+        env.borrow_mut().define("this", self.this.clone());
+        interpreter.execute_fun_call(&self.method.body, &self.method.params, env, args)
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(self.method.params.len() as u8)
+    }
+
+    fn id(&self) -> FnId {
+        // Not registered: a bound method is rebuilt fresh every time
+        // `this.method` is evaluated, so `equals_callable` below compares
+        // the underlying method and receiver directly instead.
+        FnId::UNBOUND
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        Box::new(self.clone())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn equals_callable(&self, other: &dyn Callable) -> bool {
+        other
+            .as_any()
+            .downcast_ref::<BoundMethod>()
+            .map_or(false, |a| self == a)
+    }
+}
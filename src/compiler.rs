@@ -0,0 +1,498 @@
+//! Compiles the resolved AST into the flat [`Chunk`] bytecode consumed by
+//! [`crate::vm::Vm`]. This is a second execution backend alongside the
+//! tree-walking `Interpreter`; it exists purely for speed on hot loops like
+//! the `fib` benchmark, and must agree with the tree-walker on results.
+
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::ast::*;
+use crate::chunk::{Chunk, OpCode, VmFunction};
+use crate::value::Value;
+
+/// A local variable known to the compiler at a given lexical depth. Unlike
+/// the tree-walking `Resolver`, which only needs a scope *distance*, the VM
+/// needs a concrete stack slot, so the compiler tracks its own locals table
+/// per function being compiled.
+struct Local {
+    name: String,
+    depth: usize,
+}
+
+struct FunctionCompiler {
+    chunk: Chunk,
+    locals: Vec<Local>,
+    scope_depth: usize,
+    loop_starts: Vec<usize>,
+    /// Indices of `break` jumps emitted in the innermost loop, backpatched
+    /// once the loop's end is known.
+    loop_breaks: Vec<Vec<usize>>,
+}
+
+impl FunctionCompiler {
+    fn new() -> Self {
+        Self {
+            chunk: Chunk::new(),
+            locals: Vec::new(),
+            scope_depth: 0,
+            loop_starts: Vec::new(),
+            loop_breaks: Vec::new(),
+        }
+    }
+}
+
+pub struct Compiler {
+    global_names: Vec<String>,
+    current: FunctionCompiler,
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        Self {
+            global_names: Vec::new(),
+            current: FunctionCompiler::new(),
+        }
+    }
+
+    pub fn compile(mut self, stmts: &[Stmt]) -> Chunk {
+        for stmt in stmts {
+            self.stmt(stmt);
+        }
+        self.current.chunk.global_count = self.global_names.len();
+        self.current.chunk
+    }
+
+    fn global_slot(&mut self, name: &str) -> usize {
+        if let Some(ix) = self.global_names.iter().position(|n| n == name) {
+            ix
+        } else {
+            self.global_names.push(name.to_string());
+            self.global_names.len() - 1
+        }
+    }
+
+    fn resolve_local(&self, name: &str) -> Option<usize> {
+        self.current
+            .locals
+            .iter()
+            .rposition(|local| local.name == name)
+    }
+
+    fn begin_scope(&mut self) {
+        self.current.scope_depth += 1;
+    }
+
+    fn end_scope(&mut self, span: Range<usize>) {
+        self.current.scope_depth -= 1;
+        while let Some(last) = self.current.locals.last() {
+            if last.depth > self.current.scope_depth {
+                self.current.locals.pop();
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) {
+        let span = 0..0;
+        match stmt {
+            Stmt::Expr(expr) => {
+                self.expr(expr);
+                self.current.chunk.emit(OpCode::Pop, span);
+            }
+            Stmt::Print(expr) => {
+                self.expr(expr);
+                self.current.chunk.emit(OpCode::Print, span);
+            }
+            Stmt::Var { name, initializer } => {
+                match initializer {
+                    Some(init) => self.expr(init),
+                    None => {
+                        let ix = self.current.chunk.add_constant(Value::Nil);
+                        self.current.chunk.emit(OpCode::Constant(ix), span.clone());
+                    }
+                }
+
+                if self.current.scope_depth == 0 {
+                    let slot = self.global_slot(name);
+                    self.current.chunk.emit(OpCode::DefineGlobal(slot), span);
+                } else {
+                    self.current.locals.push(Local {
+                        name: name.clone(),
+                        depth: self.current.scope_depth,
+                    });
+                }
+            }
+            Stmt::Block(stmts) => {
+                self.begin_scope();
+                for s in stmts {
+                    self.stmt(s);
+                }
+                self.end_scope(span);
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expr(condition);
+                let then_jump = self
+                    .current
+                    .chunk
+                    .emit(OpCode::JumpIfFalse(0), span.clone());
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+                self.stmt(then_branch);
+
+                let else_jump = self.current.chunk.emit(OpCode::Jump(0), span.clone());
+                self.patch_jump(then_jump);
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+
+                if let Some(else_branch) = else_branch {
+                    self.stmt(else_branch);
+                }
+                self.patch_jump(else_jump);
+            }
+            Stmt::While { condition, body } => {
+                let loop_start = self.current.chunk.code.len();
+                self.current.loop_starts.push(loop_start);
+                self.current.loop_breaks.push(Vec::new());
+
+                self.expr(condition);
+                let exit_jump = self
+                    .current
+                    .chunk
+                    .emit(OpCode::JumpIfFalse(0), span.clone());
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+
+                self.stmt(body);
+
+                // `+ 1` accounts for `Loop`'s own slot: by the time `Vm::run`
+                // executes it, `ip` has already advanced past it, so the
+                // offset needs to cover that instruction too, not just the
+                // condition-through-body span before it.
+                let offset = (self.current.chunk.code.len() + 1 - loop_start) as isize;
+                self.current.chunk.emit(OpCode::Loop(offset), span.clone());
+                self.patch_jump(exit_jump);
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+
+                self.current.loop_starts.pop();
+                for break_jump in self.current.loop_breaks.pop().unwrap() {
+                    self.patch_jump(break_jump);
+                }
+            }
+            Stmt::Break => {
+                let jump = self.current.chunk.emit(OpCode::Jump(0), span);
+                self.current
+                    .loop_breaks
+                    .last_mut()
+                    .expect("break outside loop should be rejected by the parser")
+                    .push(jump);
+            }
+            Stmt::Return(expr) => {
+                match expr {
+                    Some(expr) => self.expr(expr),
+                    None => {
+                        let ix = self.current.chunk.add_constant(Value::Nil);
+                        self.current.chunk.emit(OpCode::Constant(ix), span.clone());
+                    }
+                }
+                self.current.chunk.emit(OpCode::Return, span);
+            }
+            Stmt::Function(Function { name, params, body }) => {
+                // `compiler::unsupported_feature` rejects any `fun`
+                // declaration that isn't a direct top-level statement
+                // before this ever runs — a nested one would need to
+                // capture its enclosing locals, which this backend has no
+                // representation for yet (contrast `class::BoundMethod`'s
+                // `closure` field on the tree-walking side).
+                assert_eq!(
+                    self.current.scope_depth, 0,
+                    "nested function declarations should have been rejected by \
+                     unsupported_feature before compiling"
+                );
+
+                let arity = params.len() as u8;
+                let enclosing = std::mem::replace(&mut self.current, FunctionCompiler::new());
+
+                self.begin_scope();
+                for param in params {
+                    self.current.locals.push(Local {
+                        name: param.clone(),
+                        depth: self.current.scope_depth,
+                    });
+                }
+                for s in body {
+                    self.stmt(s);
+                }
+                // A body that falls off the end without an explicit
+                // `return` implicitly returns `nil`.
+                let nil = self.current.chunk.add_constant(Value::Nil);
+                self.current.chunk.emit(OpCode::Constant(nil), span.clone());
+                self.current.chunk.emit(OpCode::Return, span.clone());
+
+                let compiled = std::mem::replace(&mut self.current, enclosing);
+                let proto = Value::VmFunction(Rc::new(VmFunction {
+                    name: name.clone(),
+                    arity,
+                    chunk: compiled.chunk,
+                }));
+                let ix = self.current.chunk.add_constant(proto);
+                self.current.chunk.emit(OpCode::Constant(ix), span.clone());
+
+                let slot = self.global_slot(name);
+                self.current.chunk.emit(OpCode::DefineGlobal(slot), span);
+            }
+            Stmt::Class(_) => {
+                // Classes need heap-allocated instances and dynamic method
+                // dispatch, neither of which this backend has wired up yet
+                // — same gap as the `Stmt::Function` arm above.
+                // `unsupported_feature` rejects programs containing `class`
+                // before compiling, so `eval_vm` never reaches this arm.
+            }
+        }
+    }
+
+    fn patch_jump(&mut self, at: usize) {
+        let offset = (self.current.chunk.code.len() - at - 1) as isize;
+        self.current.chunk.patch_jump(at, offset);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        let span = 0..0;
+        match expr {
+            Expr::Literal(lit) => self.literal(lit, span),
+            Expr::Grouping(Grouping { expr }) => self.expr(expr),
+            Expr::Unary(Unary { op, right }) => {
+                self.expr(right);
+                match op {
+                    UnaryOp::Negation => self.current.chunk.emit(OpCode::Negate, span),
+                    UnaryOp::Not => self.current.chunk.emit(OpCode::Not, span),
+                };
+            }
+            Expr::Binary(Binary { left, op, right }) => self.binary(left, op.clone(), right, span),
+            Expr::Assign(Assign { name, value }) => {
+                self.expr(value);
+                if let Some(slot) = self.resolve_local(&name.ident) {
+                    self.current.chunk.emit(OpCode::SetLocal(slot), span);
+                } else {
+                    let slot = self.global_slot(&name.ident);
+                    self.current.chunk.emit(OpCode::SetGlobal(slot), span);
+                }
+            }
+            Expr::Call(Call { callee, args, .. }) => {
+                self.expr(callee);
+                for arg in args {
+                    self.expr(arg);
+                }
+                self.current
+                    .chunk
+                    .emit(OpCode::Call(args.len() as u8), span);
+            }
+            Expr::Get(_) | Expr::Set(_) => {
+                // Property access needs the tree-walker's `Instance`
+                // representation; see the `Stmt::Class` arm in `stmt` above.
+                unimplemented!("property access is only supported by the tree-walking backend")
+            }
+            Expr::ArrayLit(_) | Expr::Index(_) | Expr::IndexSet(_) => {
+                // Arrays need the tree-walker's `Rc<RefCell<Vec<Value>>>`
+                // representation, which this backend's `Value` doesn't carry
+                // yet; same gap as the `Get`/`Set` arm above.
+                unimplemented!("arrays are only supported by the tree-walking backend")
+            }
+            Expr::Ternary(Ternary {
+                cond,
+                then_branch,
+                else_branch,
+            }) => {
+                self.expr(cond);
+                let then_jump = self
+                    .current
+                    .chunk
+                    .emit(OpCode::JumpIfFalse(0), span.clone());
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+                self.expr(then_branch);
+
+                let else_jump = self.current.chunk.emit(OpCode::Jump(0), span.clone());
+                self.patch_jump(then_jump);
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+
+                self.expr(else_branch);
+                self.patch_jump(else_jump);
+            }
+        }
+    }
+
+    fn literal(&mut self, lit: &Literal, span: Range<usize>) {
+        match lit {
+            Literal::NumLit(NumLit(n)) => {
+                let ix = self
+                    .current
+                    .chunk
+                    .add_constant(crate::interpreter::parse_num_lit(n));
+                self.current.chunk.emit(OpCode::Constant(ix), span);
+            }
+            Literal::StringLit(StringLit(s)) => {
+                let ix = self.current.chunk.add_constant(Value::string(s.clone()));
+                self.current.chunk.emit(OpCode::Constant(ix), span);
+            }
+            Literal::Bool(b) => {
+                let ix = self.current.chunk.add_constant(Value::Bool(*b));
+                self.current.chunk.emit(OpCode::Constant(ix), span);
+            }
+            Literal::Nil => {
+                let ix = self.current.chunk.add_constant(Value::Nil);
+                self.current.chunk.emit(OpCode::Constant(ix), span);
+            }
+            Literal::Identifier(Reference { ident, .. }) => {
+                if let Some(slot) = self.resolve_local(ident) {
+                    self.current.chunk.emit(OpCode::GetLocal(slot), span);
+                } else {
+                    let slot = self.global_slot(ident);
+                    self.current.chunk.emit(OpCode::GetGlobal(slot), span);
+                }
+            }
+        }
+    }
+
+    fn binary(&mut self, left: &Expr, op: BinaryOp, right: &Expr, span: Range<usize>) {
+        match op {
+            BinaryOp::LogicAnd => {
+                self.expr(left);
+                let end = self
+                    .current
+                    .chunk
+                    .emit(OpCode::JumpIfFalse(0), span.clone());
+                self.current.chunk.emit(OpCode::Pop, span);
+                self.expr(right);
+                self.patch_jump(end);
+                return;
+            }
+            BinaryOp::LogicOr => {
+                self.expr(left);
+                let else_jump = self
+                    .current
+                    .chunk
+                    .emit(OpCode::JumpIfFalse(0), span.clone());
+                let end_jump = self.current.chunk.emit(OpCode::Jump(0), span.clone());
+                self.patch_jump(else_jump);
+                self.current.chunk.emit(OpCode::Pop, span.clone());
+                self.expr(right);
+                self.patch_jump(end_jump);
+                return;
+            }
+            _ => {}
+        }
+
+        self.expr(left);
+        self.expr(right);
+
+        match op {
+            BinaryOp::Add => self.current.chunk.emit(OpCode::Add, span),
+            BinaryOp::Sub => self.current.chunk.emit(OpCode::Sub, span),
+            BinaryOp::Mul => self.current.chunk.emit(OpCode::Mul, span),
+            BinaryOp::Div => self.current.chunk.emit(OpCode::Div, span),
+            BinaryOp::Mod => self.current.chunk.emit(OpCode::Mod, span),
+            BinaryOp::Eq => self.current.chunk.emit(OpCode::Equal, span),
+            BinaryOp::NotEq => {
+                self.current.chunk.emit(OpCode::Equal, span.clone());
+                self.current.chunk.emit(OpCode::Not, span)
+            }
+            BinaryOp::Lt => self.current.chunk.emit(OpCode::Less, span),
+            BinaryOp::Gt => self.current.chunk.emit(OpCode::Greater, span),
+            BinaryOp::Lte => {
+                self.current.chunk.emit(OpCode::Greater, span.clone());
+                self.current.chunk.emit(OpCode::Not, span)
+            }
+            BinaryOp::Gte => {
+                self.current.chunk.emit(OpCode::Less, span.clone());
+                self.current.chunk.emit(OpCode::Not, span)
+            }
+            BinaryOp::LogicAnd | BinaryOp::LogicOr => unreachable!("handled above"),
+            BinaryOp::PipeForward | BinaryOp::PipeMap => {
+                // This backend has no pipe desugaring; `unsupported_feature`
+                // rejects programs that use pipes before compiling, so
+                // `eval_vm` never reaches this arm.
+                unimplemented!("pipe operators are only supported by the tree-walking backend")
+            }
+        };
+    }
+}
+
+impl Default for Compiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Walks the resolved AST looking for constructs this backend can't
+/// actually run — the `unimplemented!()`/no-op arms in `stmt`/`expr`/
+/// `binary` above, plus nested (closure-capturing) function declarations,
+/// which `Compiler::stmt`'s `Stmt::Function` arm can't compile (see its
+/// doc comment). Top-level functions and the calls that invoke them are
+/// fine: `eval_vm` runs this before compiling so anything it can't handle
+/// gets one clear error up front instead of a panic or a `NotCallable`
+/// partway through execution.
+pub(crate) fn unsupported_feature(stmts: &[Stmt]) -> Option<&'static str> {
+    fn in_stmt(s: &Stmt, top_level: bool) -> Option<&'static str> {
+        match s {
+            Stmt::Function(Function { body, .. }) => {
+                if !top_level {
+                    return Some("nested function declarations");
+                }
+                // The body's own statements are no longer top-level, even
+                // though the declaration itself is.
+                body.iter().find_map(|s| in_stmt(s, false))
+            }
+            Stmt::Class(_) => Some("class declarations"),
+            Stmt::Block(stmts) => stmts.iter().find_map(|s| in_stmt(s, false)),
+            Stmt::Expr(e) | Stmt::Print(e) => in_expr(e),
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => in_expr(condition)
+                .or_else(|| in_stmt(then_branch, top_level))
+                .or_else(|| {
+                    else_branch
+                        .as_deref()
+                        .and_then(|b| in_stmt(b, top_level))
+                }),
+            Stmt::Return(e) => e.as_ref().and_then(in_expr),
+            Stmt::Var { initializer, .. } => initializer.as_ref().and_then(in_expr),
+            Stmt::While { condition, body } => {
+                in_expr(condition).or_else(|| in_stmt(body, top_level))
+            }
+            Stmt::Break => None,
+        }
+    }
+
+    fn in_expr(e: &Expr) -> Option<&'static str> {
+        match e {
+            Expr::Call(Call { callee, args, .. }) => {
+                in_expr(callee).or_else(|| args.iter().find_map(in_expr))
+            }
+            Expr::Get(_) | Expr::Set(_) => Some("property access"),
+            Expr::ArrayLit(_) | Expr::Index(_) | Expr::IndexSet(_) => Some("arrays"),
+            Expr::Binary(Binary { left, op, right }) => {
+                if matches!(op, BinaryOp::PipeForward | BinaryOp::PipeMap) {
+                    return Some("pipe operators");
+                }
+                in_expr(left).or_else(|| in_expr(right))
+            }
+            Expr::Assign(Assign { value, .. }) => in_expr(value),
+            Expr::Unary(Unary { right, .. }) => in_expr(right),
+            Expr::Grouping(Grouping { expr }) => in_expr(expr),
+            Expr::Ternary(Ternary {
+                cond,
+                then_branch,
+                else_branch,
+            }) => in_expr(cond)
+                .or_else(|| in_expr(then_branch))
+                .or_else(|| in_expr(else_branch)),
+            Expr::Literal(_) => None,
+        }
+    }
+
+    stmts.iter().find_map(|s| in_stmt(s, true))
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use crate::token::SpannedToken;
+use crate::token::{Position, SpannedToken};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Error<'src, E> {
@@ -15,6 +15,10 @@ impl<'src, E: Display + std::error::Error> Error<'src, E> {
             error_kind,
         }
     }
+
+    pub fn error_kind(&self) -> &E {
+        &self.error_kind
+    }
 }
 
 pub trait ErrorReporter {
@@ -24,17 +28,38 @@ pub trait ErrorReporter {
 pub struct SimpleReporter;
 
 impl ErrorReporter for SimpleReporter {
-    fn report<E: std::error::Error>(&self, _source: &str, e: &Error<'_, E>) {
-        // TODO: calculate the line number at least
-        // bonus points: print a source code fragment and point to the problematic span
+    fn report<E: std::error::Error>(&self, source: &str, e: &Error<'_, E>) {
         match &e.token {
-            Some(token) => {
-                eprintln!(
-                    "Error in span {:?}, token {:?}: {}",
-                    token.span, token.token, e.error_kind
-                )
-            }
+            Some(token) => print_snippet(
+                source,
+                token.start,
+                token.span.end.saturating_sub(token.span.start).max(1),
+                &e.error_kind.to_string(),
+            ),
             None => eprintln!("Error: {}", e.error_kind),
         }
     }
 }
+
+fn line_text(source: &str, line: usize) -> &str {
+    source.lines().nth(line - 1).unwrap_or("")
+}
+
+/// Renders a compiler-diagnostic-style snippet: the `line:col` position, a
+/// gutter, the offending physical line, and a caret row under the span.
+/// `start` comes straight off the token's already-resolved `Position` (see
+/// `lexer::LineIndex`); `span_len` is the token's length in bytes, so the
+/// caret row is as wide as the span's byte length.
+fn print_snippet(source: &str, start: Position, span_len: usize, message: &str) {
+    let text = line_text(source, start.line);
+    let gutter = format!("{} | ", start.line);
+
+    eprintln!("error at {}: {}", start, message);
+    eprintln!("{}{}", gutter, text);
+    eprintln!(
+        "{}{}{}",
+        " ".repeat(gutter.len()),
+        " ".repeat(start.col.saturating_sub(1)),
+        "^".repeat(span_len)
+    );
+}
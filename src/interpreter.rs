@@ -1,42 +1,171 @@
 use std::cell::RefCell;
 use std::io::{stdout, Stdout, Write};
+use std::ops::Range;
 use std::rc::Rc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
-use crate::callable::Clock;
+use crate::callable::{Arity, Callable, Clock, Monotonic};
 use crate::env::{Env, EnvError};
-use crate::resolver::Resolver;
-use crate::value::{Type, Value};
+use crate::value::{Rational, Type, Value};
 use crate::{ast::*, ResolverError};
 
-macro_rules! impl_arithmetic {
-    ($self:tt $left:tt $op:tt $right:tt) => {
-        match ($self.interpret_expr($left)?, $self.interpret_expr($right)?) {
-            (Num($left), Num($right)) => Num($left $op $right),
-            (v, Num(_)) => return Err(InterpreterError::TypeError{
-                expected: &[Type::Num],
-                found: v.ty(),
-            }),
-            (_, v) => return Err(InterpreterError::TypeError{
-                expected: &[Type::Num],
-                found: v.ty(),
-            }),
+/// These arithmetic operators follow the same int/rational/float promotion
+/// lattice; `Add` is handled separately in `interpret_expr` because it also
+/// overloads onto `String` for concatenation.
+#[derive(Clone, Copy)]
+pub(crate) enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+/// Applies a numeric pair through the promotion lattice: `Int op Int` stays `Int` unless `/` doesn't divide evenly
+/// (then it promotes to an exact `Rational`) or it overflows `i64` (then it
+/// promotes to `Num`, since `Rational`'s fields are `i64` too and can't hold
+/// a sum/product that already doesn't fit); anything paired with a
+/// `Rational` stays `Rational`; anything paired with a float `Num` promotes
+/// to `Num`.
+pub(crate) fn apply_arith(
+    op: ArithOp,
+    left: Value,
+    right: Value,
+) -> Result<Value, InterpreterError> {
+    use Value::*;
+    // Aliased: `use Value::*` above also brings the `Rational` tuple-variant
+    // constructor into scope, which would otherwise shadow this type import
+    // everywhere below that calls `Rational::new(...)`.
+    use crate::value::Rational as Rat;
+
+    if !is_numeric(&left) {
+        return Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: left.ty(),
+        });
+    }
+    if !is_numeric(&right) {
+        return Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: right.ty(),
+        });
+    }
+
+    match (left, right) {
+        (Num(a), b) => Ok(Num(apply_f64(op, a, as_f64(&b).unwrap()))),
+        (a, Num(b)) => Ok(Num(apply_f64(op, as_f64(&a).unwrap(), b))),
+        (Int(a), Int(b)) => match op {
+            ArithOp::Add => Ok(a
+                .checked_add(b)
+                .map(Int)
+                .unwrap_or_else(|| Num(a as f64 + b as f64))),
+            ArithOp::Sub => Ok(a
+                .checked_sub(b)
+                .map(Int)
+                .unwrap_or_else(|| Num(a as f64 - b as f64))),
+            ArithOp::Mul => Ok(a
+                .checked_mul(b)
+                .map(Int)
+                .unwrap_or_else(|| Num(a as f64 * b as f64))),
+            ArithOp::Div => {
+                if b == 0 {
+                    return Err(InterpreterError::DivisionByZero);
+                }
+                if a % b == 0 {
+                    Ok(Int(a / b))
+                } else {
+                    Ok(Rational(Rat::new(a, b)))
+                }
+            }
+            ArithOp::Mod => {
+                if b == 0 {
+                    return Err(InterpreterError::DivisionByZero);
+                }
+                Ok(Int(a % b))
+            }
+        },
+        (Rational(a), Int(b)) => apply_rational(op, a, Rat::new(b, 1)),
+        (Int(a), Rational(b)) => apply_rational(op, Rat::new(a, 1), b),
+        (Rational(a), Rational(b)) => apply_rational(op, a, b),
+        (v, _) => Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: v.ty(),
+        }),
+    }
+}
+
+fn apply_rational(op: ArithOp, a: Rational, b: Rational) -> Result<Value, InterpreterError> {
+    let result = match op {
+        ArithOp::Add => a + b,
+        ArithOp::Sub => a - b,
+        ArithOp::Mul => a * b,
+        ArithOp::Div => {
+            if b.num == 0 {
+                return Err(InterpreterError::DivisionByZero);
+            }
+            a / b
+        }
+        ArithOp::Mod => {
+            if b.num == 0 {
+                return Err(InterpreterError::DivisionByZero);
+            }
+            // `a - trunc(a / b) * b`, where truncation uses integer `/`
+            // (which already rounds toward zero), so the remainder stays
+            // an exact `Rational` instead of going through `f64`.
+            let quotient = a / b;
+            let truncated = Rational::new(quotient.num / quotient.den, 1);
+            a - truncated * b
         }
     };
+
+    Ok(result
+        .as_int()
+        .map(Value::Int)
+        .unwrap_or(Value::Rational(result)))
+}
+
+fn apply_f64(op: ArithOp, a: f64, b: f64) -> f64 {
+    match op {
+        ArithOp::Add => a + b,
+        ArithOp::Sub => a - b,
+        ArithOp::Mul => a * b,
+        ArithOp::Div => a / b,
+        ArithOp::Mod => a % b,
+    }
+}
+
+fn is_numeric(v: &Value) -> bool {
+    matches!(v, Value::Int(_) | Value::Rational(_) | Value::Num(_))
+}
+
+/// Widens any numeric `Value` to `f64`, used for comparisons (which, unlike
+/// arithmetic, don't need to stay exact) and for float-promoted arithmetic.
+fn as_f64(v: &Value) -> Option<f64> {
+    match v {
+        Value::Int(n) => Some(*n as f64),
+        Value::Rational(r) => Some(r.to_f64()),
+        Value::Num(n) => Some(*n),
+        _ => None,
+    }
+}
+
+pub(crate) fn numeric_compare(left: Value, right: Value) -> Result<f64, InterpreterError> {
+    match (as_f64(&left), as_f64(&right)) {
+        (Some(a), Some(b)) => Ok(a - b),
+        (None, _) => Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: left.ty(),
+        }),
+        (_, None) => Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: right.ty(),
+        }),
+    }
 }
 
 macro_rules! impl_comparison {
     ($self:tt $left:tt $op:tt $right:tt) => {
-        match ($self.interpret_expr($left)?, $self.interpret_expr($right)?) {
-            (Num($left), Num($right)) => Bool($left $op $right),
-            (v, Num(_)) => return Err(InterpreterError::TypeError{
-                expected: &[Type::Num],
-                found: v.ty(),
-            }),
-            (_, v) => return Err(InterpreterError::TypeError{
-                expected: &[Type::Num],
-                found: v.ty(),
-            }),
-        }
+        Bool(numeric_compare($self.interpret_expr($left)?, $self.interpret_expr($right)?)? $op 0.0)
     };
 }
 
@@ -44,7 +173,37 @@ pub struct Interpreter {
     globals: Rc<RefCell<Env>>,
     env: Rc<RefCell<Env>>,
     out: InterpreterOutput,
-    resolver: Resolver,
+    /// `locals[ref_id]` is the scope distance `resolver::resolve` computed
+    /// for that reference's id, or `None` for a global. Populated once per
+    /// `interpret` call and read back by `look_up_variable`.
+    resolved_locals: Vec<Option<usize>>,
+    /// Every callable ever created, keyed by the `FnId` it was handed at
+    /// construction (see `callable::register_callable`). `equals_callable`
+    /// compares these ids instead of downcasting and structurally
+    /// comparing a callable's fields.
+    fn_registry: Vec<Box<dyn Callable>>,
+    /// SplitMix64 state backing `random`/`randomInt`/`randomBool` (see
+    /// `stdlib.rs`). Seeded from wall-clock time by default so runs differ
+    /// unless `seedRandom` is called; kept on the interpreter rather than
+    /// thread-local/global state so that unrelated `Interpreter` instances
+    /// (e.g. two tests running in the same process) never share a stream.
+    rng_state: u64,
+    /// Gates `readFile`/`writeFile`/`appendFile`/`tempFile` (see
+    /// `stdlib.rs`). Defaults to `true`; embedders running untrusted Lox
+    /// should build with [`Interpreter::with_allow_io`] instead so those
+    /// natives error out rather than touch the filesystem.
+    allow_io: bool,
+    /// Paths handed out by `tempFile`, deleted on `Drop` so a long-lived
+    /// embedder doesn't accumulate scratch files across runs.
+    temp_files: Vec<std::path::PathBuf>,
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        for path in &self.temp_files {
+            let _ = std::fs::remove_file(path);
+        }
+    }
 }
 
 pub enum ExecResult {
@@ -52,48 +211,113 @@ pub enum ExecResult {
     LoopUnwind,
 }
 
-fn make_global_env() -> Rc<RefCell<Env>> {
+/// Seeds the SplitMix64 state from wall-clock time, so two interpreters
+/// started moments apart don't produce the same `random()` stream unless
+/// `seedRandom` is called explicitly.
+fn default_rng_seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn make_global_env(fn_registry: &mut Vec<Box<dyn Callable>>, start: Instant) -> Rc<RefCell<Env>> {
     let env = Env::new();
 
+    let clock = crate::callable::register_callable(fn_registry, Clock::new);
+    env.borrow_mut().define("clock", Value::Callable(Rc::new(clock)));
+    let monotonic =
+        crate::callable::register_callable(fn_registry, |id| Monotonic::new(id, start));
     env.borrow_mut()
-        .define("clock", Value::Callable(Rc::new(Clock)));
+        .define("monotonic", Value::Callable(Rc::new(monotonic)));
+    crate::stdlib::load(&env, fn_registry);
 
     env
 }
 
 impl Default for Interpreter {
     fn default() -> Self {
-        let env = make_global_env();
+        let mut fn_registry = Vec::new();
+        let env = make_global_env(&mut fn_registry, Instant::now());
 
         Self {
             globals: Rc::clone(&env),
             env,
             out: InterpreterOutput::Stdout(stdout()),
-            resolver: Resolver::new(),
+            resolved_locals: Vec::new(),
+            fn_registry,
+            rng_state: default_rng_seed(),
+            allow_io: true,
+            temp_files: Vec::new(),
         }
     }
 }
 
 impl Interpreter {
     pub fn new(out: InterpreterOutput) -> Self {
-        let env = make_global_env();
+        let mut fn_registry = Vec::new();
+        let env = make_global_env(&mut fn_registry, Instant::now());
 
         Self {
             globals: Rc::clone(&env),
             env,
             out,
-            resolver: Resolver::new(),
+            resolved_locals: Vec::new(),
+            fn_registry,
+            rng_state: default_rng_seed(),
+            allow_io: true,
+            temp_files: Vec::new(),
         }
     }
 
+    /// Builds an interpreter with filesystem access explicitly gated, for
+    /// embedders running untrusted Lox that want `Clock`/math/random
+    /// natives available but `readFile`/`writeFile`/`appendFile`/`tempFile`
+    /// turned off.
+    pub fn with_allow_io(out: InterpreterOutput, allow_io: bool) -> Self {
+        let mut interpreter = Self::new(out);
+        interpreter.allow_io = allow_io;
+        interpreter
+    }
+
+    pub(crate) fn allow_io(&self) -> bool {
+        self.allow_io
+    }
+
+    /// Registers `path` for deletion when this interpreter drops. Called by
+    /// `tempFile` right after it creates the file.
+    pub(crate) fn track_temp_file(&mut self, path: std::path::PathBuf) {
+        self.temp_files.push(path);
+    }
+
+    /// Advances the SplitMix64 state and returns its next output word. See
+    /// `seed_rng` to make the stream reproducible.
+    pub(crate) fn next_rng_u64(&mut self) -> u64 {
+        self.rng_state = self.rng_state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.rng_state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform `f64` in `[0, 1)`, taken from the top 53 bits of a
+    /// SplitMix64 word (an `f64`'s mantissa is 53 bits wide, so this is the
+    /// finest resolution a `[0, 1)` float can represent).
+    pub(crate) fn next_rng_f64(&mut self) -> f64 {
+        (self.next_rng_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Reseeds the RNG so the stream from this point on is reproducible.
+    pub(crate) fn seed_rng(&mut self, seed: u64) {
+        self.rng_state = seed;
+    }
+
     pub fn interpret(
         &mut self,
         stmts: &[Stmt],
         var_count: usize,
     ) -> Result<Value, Vec<InterpreterError>> {
-        self.resolver
-            .resolve(stmts, var_count)
-            .map_err(|err| vec![err.into()])?;
+        self.resolved_locals = crate::resolver::resolve(stmts, var_count).map_err(|err| vec![err.into()])?;
 
         let errs = stmts
             .iter()
@@ -160,21 +384,73 @@ impl Interpreter {
             }
             Stmt::Break => return Err(InterpreterError::LoopUnwind),
             Stmt::Function(fun) => self.declare_fun(fun),
+            Stmt::Class(class) => self.declare_class(class)?,
         };
 
         Ok(())
     }
 
-    fn declare_fun(&mut self, fun: &Function) {
-        let fun_env = Rc::clone(&self.env);
+    fn declare_class(
+        &mut self,
+        Class {
+            name,
+            superclass,
+            methods,
+        }: &Class,
+    ) -> Result<(), InterpreterError> {
+        let superclass = match superclass {
+            Some(reference) => match self.look_up_variable(reference) {
+                Value::Class(class) => Some(class),
+                v => {
+                    return Err(InterpreterError::TypeError {
+                        expected: &[Type::Class],
+                        found: v.ty(),
+                    })
+                }
+            },
+            None => None,
+        };
 
-        self.env.borrow_mut().define(
-            fun.name.clone(),
-            Value::Callable(Rc::new(crate::callable::Function::new(
-                fun.clone(),
-                fun_env,
-            ))),
-        );
+        // Bound methods nest their `this` scope onto this `closure`: a
+        // fresh scope binding `super` to the superclass, or `globals` when
+        // there's no superclass to bind. See `Resolver::resolve_class_decl`
+        // for the matching distance computation.
+        let closure = match &superclass {
+            Some(superclass) => {
+                let env = Env::child(&self.env);
+                env.borrow_mut()
+                    .define("super", Value::Class(Rc::clone(superclass)));
+                env
+            }
+            None => Rc::clone(&self.globals),
+        };
+
+        let methods = methods
+            .iter()
+            .map(|method| (method.name.clone(), method.clone()))
+            .collect();
+
+        let class = Value::Class(Rc::new(crate::class::Class {
+            name: name.clone(),
+            superclass,
+            methods,
+            closure,
+        }));
+
+        self.env.borrow_mut().define(name.clone(), class);
+
+        Ok(())
+    }
+
+    fn declare_fun(&mut self, fun: &Function) {
+        let closure = Rc::clone(&self.env);
+        let f = crate::callable::register_callable(&mut self.fn_registry, |id| {
+            crate::callable::Function::new(fun.clone(), id, closure)
+        });
+
+        self.env
+            .borrow_mut()
+            .define(fun.name.clone(), Value::Callable(Rc::new(f)));
     }
 
     pub fn execute_fun_call(
@@ -247,6 +523,10 @@ impl Interpreter {
 
     pub fn interpret_expr(&mut self, expr: &Expr) -> Result<Value, InterpreterError> {
         use Value::*;
+        // See the matching alias in `apply_arith`: `Value::*` shadows the
+        // `Rational` type import wherever this scope also needs to call
+        // `Rational::new(...)`.
+        use crate::value::Rational as Rat;
 
         Ok(match expr {
             Expr::Assign(Assign {
@@ -261,7 +541,7 @@ impl Interpreter {
             }
             Expr::Literal(l) => match l {
                 Literal::StringLit(StringLit(l)) => Value::string(l),
-                Literal::NumLit(NumLit(l)) => Num(l.parse().unwrap()),
+                Literal::NumLit(NumLit(l)) => parse_num_lit(l),
                 Literal::Identifier(reference) => self.look_up_variable(reference),
                 Literal::Nil => Value::Nil,
                 Literal::Bool(b) => Value::Bool(*b),
@@ -270,17 +550,19 @@ impl Interpreter {
                 BinaryOp::LogicOr => self.eval_logic(true, left, right)?,
                 BinaryOp::LogicAnd => self.eval_logic(false, left, right)?,
                 BinaryOp::Add => match (self.interpret_expr(left)?, self.interpret_expr(right)?) {
-                    (Num(left), Num(right)) => Num(left + right),
                     (String(left), String(right)) => Value::string(format!("{}{}", left, right)),
-                    (Num(_), v) => {
+                    (String(_), v) => {
                         return Err(InterpreterError::TypeError {
-                            expected: &[Type::Num],
+                            expected: &[Type::String],
                             found: v.ty(),
                         })
                     }
-                    (String(_), v) => {
+                    (left, right) if is_numeric(&left) && is_numeric(&right) => {
+                        apply_arith(ArithOp::Add, left, right)?
+                    }
+                    (left, v) if is_numeric(&left) => {
                         return Err(InterpreterError::TypeError {
-                            expected: &[Type::String],
+                            expected: &[Type::Num],
                             found: v.ty(),
                         })
                     }
@@ -291,9 +573,18 @@ impl Interpreter {
                         })
                     }
                 },
-                BinaryOp::Sub => impl_arithmetic!(self  left - right),
-                BinaryOp::Mul => impl_arithmetic!(self  left * right),
-                BinaryOp::Div => impl_arithmetic!(self  left / right),
+                BinaryOp::Sub => {
+                    apply_arith(ArithOp::Sub, self.interpret_expr(left)?, self.interpret_expr(right)?)?
+                }
+                BinaryOp::Mul => {
+                    apply_arith(ArithOp::Mul, self.interpret_expr(left)?, self.interpret_expr(right)?)?
+                }
+                BinaryOp::Div => {
+                    apply_arith(ArithOp::Div, self.interpret_expr(left)?, self.interpret_expr(right)?)?
+                }
+                BinaryOp::Mod => {
+                    apply_arith(ArithOp::Mod, self.interpret_expr(left)?, self.interpret_expr(right)?)?
+                }
                 BinaryOp::Lt => impl_comparison!(self  left < right),
                 BinaryOp::Lte => impl_comparison!(self  left <= right),
                 BinaryOp::Gt => impl_comparison!(self  left > right),
@@ -306,26 +597,262 @@ impl Interpreter {
                     &self.interpret_expr(left)?,
                     &self.interpret_expr(right)?,
                 )),
+                BinaryOp::PipeForward => {
+                    let lhs = self.interpret_expr(left)?;
+                    let rhs = self.interpret_expr(right)?;
+                    self.invoke_single(rhs, lhs)?
+                }
+                BinaryOp::PipeMap => {
+                    // `|:` maps over a string's characters or an array's
+                    // elements, rebuilding a value of the same shape.
+                    let lhs = self.interpret_expr(left)?;
+                    let rhs = self.interpret_expr(right)?;
+                    match lhs {
+                        Value::String(s) => {
+                            let mut mapped = std::string::String::new();
+                            for ch in s.chars() {
+                                let result =
+                                    self.invoke_single(rhs.clone(), Value::string(ch.to_string()))?;
+                                mapped.push_str(&result.to_string());
+                            }
+                            Value::string(mapped)
+                        }
+                        Value::Array(elements) => {
+                            let elements = elements.borrow().clone();
+                            let mut mapped = Vec::with_capacity(elements.len());
+                            for element in elements {
+                                mapped.push(self.invoke_single(rhs.clone(), element)?);
+                            }
+                            Value::Array(Rc::new(RefCell::new(mapped)))
+                        }
+                        v => {
+                            return Err(InterpreterError::TypeError {
+                                expected: &[Type::String, Type::Array],
+                                found: v.ty(),
+                            })
+                        }
+                    }
+                }
             },
             Expr::Grouping(Grouping { expr }) => self.interpret_expr(expr)?,
             Expr::Unary(Unary { op, right }) => match op {
                 UnaryOp::Not => Bool(!is_truthy(&self.interpret_expr(right)?)),
                 UnaryOp::Negation => match self.interpret_expr(right)? {
                     Num(n) => Num(-n),
+                    Int(n) => Int(-n),
+                    Rational(r) => Rational(Rat::new(-r.num, r.den)),
                     v => {
                         return Err(InterpreterError::TypeError {
-                            expected: &[Type::Num],
+                            expected: &[Type::Num, Type::Int, Type::Rational],
                             found: v.ty(),
                         })
                     }
                 },
             },
             Expr::Call(c) => self.interpret_call(c)?,
+            Expr::Get(Get { object, name }) => self.interpret_get(object, name)?,
+            Expr::Set(Set {
+                object,
+                name,
+                value,
+            }) => self.interpret_set(object, name, value)?,
+            Expr::ArrayLit(ArrayLit { elements }) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements {
+                    values.push(self.interpret_expr(element)?);
+                }
+                Value::Array(Rc::new(RefCell::new(values)))
+            }
+            Expr::Index(Index { object, index, .. }) => self.interpret_index(object, index)?,
+            Expr::IndexSet(IndexSet {
+                object,
+                index,
+                value,
+            }) => self.interpret_index_set(object, index, value)?,
+            Expr::Ternary(Ternary {
+                cond,
+                then_branch,
+                else_branch,
+            }) => {
+                if is_truthy(&self.interpret_expr(cond)?) {
+                    self.interpret_expr(then_branch)?
+                } else {
+                    self.interpret_expr(else_branch)?
+                }
+            }
         })
     }
 
+    fn interpret_get(&mut self, object: &Expr, name: &str) -> Result<Value, InterpreterError> {
+        let value = self.interpret_expr(object)?;
+
+        match value {
+            Value::Instance(instance) => {
+                if let Some(field) = instance.borrow().fields.get(name).cloned() {
+                    return Ok(field);
+                }
+
+                let class = Rc::clone(&instance.borrow().class);
+                self.bind_method(&class, Value::Instance(instance), name)
+            }
+            // `super.method()` parses to the same `Get` node as any other
+            // property access; `super` just evaluates to the superclass
+            // `Value` rather than an instance, so the receiver (`this`) has
+            // to come from the currently-executing method's scope instead.
+            Value::Class(class) => {
+                let this = self.env.borrow().get("this");
+                self.bind_method(&class, this, name)
+            }
+            v => Err(InterpreterError::TypeError {
+                expected: &[Type::Instance],
+                found: v.ty(),
+            }),
+        }
+    }
+
+    fn bind_method(
+        &self,
+        class: &Rc<crate::class::Class>,
+        this: Value,
+        name: &str,
+    ) -> Result<Value, InterpreterError> {
+        class
+            .find_method(name)
+            .map(|method| {
+                Value::Callable(Rc::new(crate::class::BoundMethod {
+                    method: Rc::new(method.clone()),
+                    closure: Rc::clone(&class.closure),
+                    this,
+                }))
+            })
+            .ok_or_else(|| InterpreterError::UndefinedProperty(name.to_string()))
+    }
+
+    fn interpret_set(
+        &mut self,
+        object: &Expr,
+        name: &str,
+        value: &Expr,
+    ) -> Result<Value, InterpreterError> {
+        let object = self.interpret_expr(object)?;
+        let value = self.interpret_expr(value)?;
+
+        match object {
+            Value::Instance(instance) => {
+                instance
+                    .borrow_mut()
+                    .fields
+                    .insert(name.to_string(), value.clone());
+                Ok(value)
+            }
+            v => Err(InterpreterError::TypeError {
+                expected: &[Type::Instance],
+                found: v.ty(),
+            }),
+        }
+    }
+
+    fn interpret_index(&mut self, object: &Expr, index: &Expr) -> Result<Value, InterpreterError> {
+        let elements = match self.interpret_expr(object)? {
+            Value::Array(elements) => elements,
+            v => {
+                return Err(InterpreterError::TypeError {
+                    expected: &[Type::Array],
+                    found: v.ty(),
+                })
+            }
+        };
+
+        let index = match self.interpret_expr(index)? {
+            Value::Int(i) => i,
+            v => {
+                return Err(InterpreterError::TypeError {
+                    expected: &[Type::Int],
+                    found: v.ty(),
+                })
+            }
+        };
+
+        let elements = elements.borrow();
+        usize::try_from(index)
+            .ok()
+            .and_then(|ix| elements.get(ix).cloned())
+            .ok_or(InterpreterError::IndexOutOfBounds {
+                index,
+                len: elements.len(),
+            })
+    }
+
+    fn interpret_index_set(
+        &mut self,
+        object: &Expr,
+        index: &Expr,
+        value: &Expr,
+    ) -> Result<Value, InterpreterError> {
+        let elements = match self.interpret_expr(object)? {
+            Value::Array(elements) => elements,
+            v => {
+                return Err(InterpreterError::TypeError {
+                    expected: &[Type::Array],
+                    found: v.ty(),
+                })
+            }
+        };
+
+        let index = match self.interpret_expr(index)? {
+            Value::Int(i) => i,
+            v => {
+                return Err(InterpreterError::TypeError {
+                    expected: &[Type::Int],
+                    found: v.ty(),
+                })
+            }
+        };
+
+        let value = self.interpret_expr(value)?;
+
+        let mut elements = elements.borrow_mut();
+        let len = elements.len();
+        let slot = usize::try_from(index)
+            .ok()
+            .and_then(|ix| elements.get_mut(ix))
+            .ok_or(InterpreterError::IndexOutOfBounds { index, len })?;
+
+        *slot = value.clone();
+        Ok(value)
+    }
+
+    fn instantiate(
+        &mut self,
+        class: Rc<crate::class::Class>,
+        args: Vec<Value>,
+        span: Range<usize>,
+    ) -> Result<Value, InterpreterError> {
+        let instance = Rc::new(RefCell::new(crate::class::Instance::new(Rc::clone(&class))));
+
+        if let Some(init) = class.find_method("init") {
+            let bound = crate::class::BoundMethod {
+                method: Rc::new(init.clone()),
+                closure: Rc::clone(&class.closure),
+                this: Value::Instance(Rc::clone(&instance)),
+            };
+
+            if !bound.arity().accepts(args.len()) {
+                return Err(InterpreterError::ArityMismatch {
+                    expected: bound.arity(),
+                    got: args.len(),
+                    span,
+                });
+            }
+
+            bound.call(self, args)?;
+        }
+
+        Ok(Value::Instance(instance))
+    }
+
     fn look_up_variable(&self, Reference { id, ident }: &Reference) -> Value {
-        if let Some(Some(distance)) = self.resolver.locals.get(*id) {
+        if let Some(Some(distance)) = self.resolved_locals.get(*id) {
             self.env.borrow().get_at(*distance, ident)
         } else {
             self.globals.borrow().get(ident)
@@ -334,7 +861,7 @@ impl Interpreter {
 
     fn interpret_call(
         &mut self,
-        Call { callee, args, .. }: &Call,
+        Call { callee, args, paren }: &Call,
     ) -> Result<Value, InterpreterError> {
         let callee = self.interpret_expr(callee)?;
 
@@ -343,21 +870,74 @@ impl Interpreter {
             .map(|arg| self.interpret_expr(arg))
             .collect::<Result<_, _>>()?;
 
-        if let Value::Callable(callable) = callee {
-            if args.len() == callable.arity() as usize {
-                Ok(callable.call(self, args)?)
-            } else {
-                Err(InterpreterError::ArityMismatch {
-                    expected: callable.arity(),
-                    got: args.len(),
-                })
+        self.invoke(callee, args, paren.clone())
+    }
+
+    /// Applies a callable `Value` to a single argument, reusing the same
+    /// arity and `NotCallable` checks as a regular call expression. Used to
+    /// desugar the pipe operators, which don't go through `Expr::Call` and
+    /// so have no `paren` span of their own to blame.
+    fn invoke_single(&mut self, callee: Value, arg: Value) -> Result<Value, InterpreterError> {
+        self.invoke(callee, vec![arg], 0..0)
+    }
+
+    fn invoke(
+        &mut self,
+        callee: Value,
+        args: Vec<Value>,
+        span: Range<usize>,
+    ) -> Result<Value, InterpreterError> {
+        match callee {
+            Value::Class(class) => self.instantiate(class, args, span),
+            Value::Callable(callable) => {
+                if callable.arity().accepts(args.len()) {
+                    Ok(callable.call(self, args)?)
+                } else {
+                    Err(InterpreterError::ArityMismatch {
+                        expected: callable.arity(),
+                        got: args.len(),
+                        span,
+                    })
+                }
             }
-        } else {
-            Err(InterpreterError::NotCallable)
+            _ => Err(InterpreterError::NotCallable { span }),
+        }
+    }
+}
+
+/// The lexer already normalized the lexeme (underscores stripped) and
+/// validated its shape, so parsing it can't fail. A `0x`/`0b` prefix means
+/// an integer in that radix; otherwise a `.`/exponent means a float, and
+/// anything else is a plain decimal integer.
+pub(crate) fn parse_num_lit(lexeme: &str) -> Value {
+    if let Some(digits) = lexeme.strip_prefix("0x").or_else(|| lexeme.strip_prefix("0X")) {
+        parse_radix_int(digits, 16)
+    } else if let Some(digits) = lexeme.strip_prefix("0b").or_else(|| lexeme.strip_prefix("0B")) {
+        parse_radix_int(digits, 2)
+    } else if lexeme.contains(['.', 'e', 'E']) {
+        Value::Num(lexeme.parse().unwrap())
+    } else {
+        match lexeme.parse() {
+            Ok(n) => Value::Int(n),
+            Err(_) => Value::Num(lexeme.parse().unwrap_or(f64::NAN)),
         }
     }
 }
 
+/// Falls back to a lossily-parsed `Value::Num` when `digits` is too wide
+/// for an `i64` (e.g. `0xFFFFFFFFFFFFFFFF`), the same way an oversized
+/// decimal literal above degrades, instead of panicking via `.unwrap()`
+/// on `i64::from_str_radix`.
+fn parse_radix_int(digits: &str, radix: u32) -> Value {
+    match i64::from_str_radix(digits, radix) {
+        Ok(n) => Value::Int(n),
+        Err(_) => match u128::from_str_radix(digits, radix) {
+            Ok(n) => Value::Num(n as f64),
+            Err(_) => Value::Num(f64::NAN),
+        },
+    }
+}
+
 fn is_truthy(val: &Value) -> bool {
     match val {
         Value::Bool(b) => *b,
@@ -405,11 +985,29 @@ pub enum InterpreterError {
     #[error("returning from function")]
     FunReturn(Value),
     #[error("not callable")]
-    NotCallable,
+    NotCallable { span: Range<usize> },
     #[error("function expected {expected} arguments, but received {got}")]
-    ArityMismatch { expected: u8, got: usize },
+    ArityMismatch {
+        expected: Arity,
+        got: usize,
+        span: Range<usize>,
+    },
     #[error("{0}")]
     Resolution(#[from] ResolverError),
+    #[error("attempted to divide by zero")]
+    DivisionByZero,
+    #[error("undefined property `{0}`")]
+    UndefinedProperty(String),
+    #[error("array index {index} out of bounds for length {len}")]
+    IndexOutOfBounds { index: i64, len: usize },
+    #[error("integer overflow")]
+    NumericOverflow,
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("file I/O is disabled for this interpreter")]
+    IoDisabled,
+    #[error("the bytecode VM backend doesn't support {0} yet; run this program with the tree-walking backend instead")]
+    UnsupportedByVm(&'static str),
 }
 
 impl InterpreterError {
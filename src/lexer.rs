@@ -2,23 +2,43 @@ use std::iter::Peekable;
 
 use logos::Logos;
 
-use crate::token::{SpannedToken, Token};
+use crate::token::{Position, SpannedToken, Token};
 
 pub struct Lexer<'src> {
     // The fact we use the `logos` lexer is an implementation detail of our `Lexer`.
     // We might want to change that in the future, so we encapsulate this detail.
     inner: Peekable<logos::SpannedIter<'src, Token<'src>>>,
+    source: &'src str,
+    lines: LineIndex,
 }
 
 impl<'src> Lexer<'src> {
     pub fn new(source: &'src str) -> Self {
         Self {
             inner: Token::lexer(source).spanned().peekable(),
+            source,
+            lines: LineIndex::new(source),
         }
     }
 
     pub fn peek(&mut self) -> Option<Token<'src>> {
-        self.inner.peek().map(|(token, _span)| *token)
+        self.inner.peek().map(|(token, _span)| token.clone())
+    }
+
+    /// Like [`Self::peek`], but resolves the token's span to a [`SpannedToken`]
+    /// instead of discarding it — for the error sites in `parser` that need a
+    /// span to attach to an `Error` without consuming the token.
+    pub fn peek_spanned(&mut self) -> Option<SpannedToken<'src>> {
+        self.inner.peek().cloned().map(|(token, span)| {
+            let start = self.lines.position(self.source, span.start);
+            let end = self.lines.position(self.source, span.end);
+            SpannedToken {
+                token,
+                span,
+                start,
+                end,
+            }
+        })
     }
 }
 
@@ -26,9 +46,56 @@ impl<'src> Iterator for Lexer<'src> {
     type Item = SpannedToken<'src>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner
-            .next()
-            .map(|(token, span)| SpannedToken { token, span })
+        self.inner.next().map(|(token, span)| {
+            let start = self.lines.position(self.source, span.start);
+            let end = self.lines.position(self.source, span.end);
+            SpannedToken {
+                token,
+                span,
+                start,
+                end,
+            }
+        })
+    }
+}
+
+/// The byte offsets of every `\n` in a source string, built once so that
+/// converting a byte offset to a `Position` is a binary search rather than
+/// a fresh linear scan per token.
+struct LineIndex {
+    newlines: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let newlines = source
+            .char_indices()
+            .filter(|&(_, c)| c == '\n')
+            .map(|(ix, _)| ix)
+            .collect();
+
+        Self { newlines }
+    }
+
+    /// Converts a byte offset into a 1-based `(line, col)` position.
+    /// `partition_point` binary-searches the sorted newline table for how
+    /// many newlines precede `byte_offset`, which is the 0-based line
+    /// index; the column is then just a `char` count from that line's
+    /// start, since offsets within a single line still need a linear scan.
+    fn position(&self, source: &str, byte_offset: usize) -> Position {
+        let byte_offset = byte_offset.min(source.len());
+        let line = self.newlines.partition_point(|&nl| nl < byte_offset);
+        let line_start = if line == 0 {
+            0
+        } else {
+            self.newlines[line - 1] + 1
+        };
+        let col = source[line_start..byte_offset].chars().count() + 1;
+
+        Position {
+            line: line + 1,
+            col,
+        }
     }
 }
 
@@ -82,12 +149,12 @@ mod tests {
                 Var,
                 Identifier("foo"),
                 Equal,
-                StringLit("asd"),
+                StringLit("asd".to_string()),
                 Semicolon,
                 Var,
                 Identifier("bar"),
                 Equal,
-                StringLit("dsa"),
+                StringLit("dsa".to_string()),
                 Semicolon,
             ],
         );
@@ -98,31 +165,94 @@ mod tests {
                 Var,
                 Identifier("foo"),
                 Equal,
-                StringLit(r#"👁💃🕺🈯️as  \n\\n \"d\""#),
+                StringLit("👁💃🕺🈯️as  \n\\n \"d\"".to_string()),
                 Semicolon,
                 Var,
                 Identifier("bar"),
                 Equal,
-                StringLit("dsa"),
+                StringLit("dsa".to_string()),
                 Semicolon,
             ],
         );
     }
 
+    #[test]
+    fn string_escapes() {
+        use Token::*;
+
+        assert_lexer(r#""\u{1F600}""#, [StringLit("\u{1F600}".to_string())]);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        use Token::*;
+
+        assert_lexer(r#"var foo = "asd"#, [Var, Identifier("foo"), Equal, UnterminatedString]);
+    }
+
+    #[test]
+    fn malformed_escape_sequence() {
+        use Token::*;
+
+        assert_lexer(r#""bad \q escape""#, [MalformedEscapeSequence]);
+    }
+
+    #[test]
+    fn malformed_number() {
+        use Token::*;
+
+        assert_lexer("1.2.3", [MalformedNumber]);
+    }
+
     #[test]
     fn integers() {
         use Token::*;
 
-        assert_lexer("324", [NumLit("324")]);
+        assert_lexer("324", [NumLit("324".to_string())]);
     }
 
     #[test]
     fn decimals() {
         use Token::*;
 
-        assert_lexer("324.5", [NumLit("324.5")]);
-        assert_lexer("324.", [NumLit("324"), Dot]);
-        assert_lexer(".5", [Dot, NumLit("5")]);
+        assert_lexer("324.5", [NumLit("324.5".to_string())]);
+        assert_lexer("324.", [NumLit("324".to_string()), Dot]);
+        assert_lexer(".5", [Dot, NumLit("5".to_string())]);
+    }
+
+    #[test]
+    fn scientific_notation() {
+        use Token::*;
+
+        assert_lexer("1.5e-3", [NumLit("1.5e-3".to_string())]);
+        assert_lexer("2E10", [NumLit("2E10".to_string())]);
+    }
+
+    #[test]
+    fn digit_separators() {
+        use Token::*;
+
+        assert_lexer("1_000_000", [NumLit("1000000".to_string())]);
+        assert_lexer("0x1a_2b", [NumLit("0x1a2b".to_string())]);
+    }
+
+    #[test]
+    fn hex_and_binary_literals() {
+        use Token::*;
+
+        assert_lexer("0x1A", [NumLit("0x1A".to_string())]);
+        assert_lexer("0b1010", [NumLit("0b1010".to_string())]);
+    }
+
+    #[test]
+    fn malformed_number_variants() {
+        use Token::*;
+
+        assert_lexer("0x", [MalformedNumber]);
+        assert_lexer("0b", [MalformedNumber]);
+        assert_lexer("1__2", [MalformedNumber]);
+        assert_lexer("1_", [MalformedNumber]);
+        assert_lexer("1e", [MalformedNumber]);
     }
 
     #[test]
@@ -1,23 +1,46 @@
 mod ast;
 mod callable;
+mod chunk;
+mod class;
+mod compiler;
 mod env;
 pub mod errors;
 pub mod interpreter;
 mod lexer;
 pub mod parser;
 mod resolver;
+pub mod stdlib;
 mod token;
+pub mod typeck;
 pub mod value;
+pub mod vm;
 
 use errors::ErrorReporter;
 use value::Value;
 
-use crate::{interpreter::Interpreter, parser::Parser};
+use crate::{
+    compiler::Compiler,
+    interpreter::{Interpreter, InterpreterOutput},
+    parser::Parser,
+    vm::Vm,
+};
 
+pub use callable::Arity;
 pub use interpreter::InterpreterError;
 pub use parser::{ParserError, ParserErrorKind};
 pub use resolver::ResolverError;
 
+/// Names one of the two execution backends: the original tree-walking
+/// `Interpreter` (run by `eval`/`eval_typechecked`), or the bytecode
+/// `Compiler`/`Vm` pair (run by `eval_vm`). The two must agree on results;
+/// the VM backend exists purely for speed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Backend {
+    #[default]
+    TreeWalk,
+    Vm,
+}
+
 pub fn eval<'src>(
     source: &'src str,
     error_reporter: impl ErrorReporter,
@@ -30,8 +53,56 @@ pub fn eval<'src>(
                 Ok(v) => Ok(v),
                 Err(errs) => {
                     for err in errs.iter() {
-                        // TODO: use the error reporter here
-                        println!("{}", err);
+                        error_reporter.report(source, &errors::Error::new(None, err.clone()));
+                    }
+
+                    Err(EvalErrors::Interpreter(errs))
+                }
+            }
+        }
+        Err(errs) => {
+            for err in errs.iter() {
+                error_reporter.report(source, err);
+            }
+
+            Err(EvalErrors::Syntax(errs))
+        }
+    }
+}
+
+/// Like `eval`, but runs the optional `typeck` pass after the `Resolver`
+/// and before interpretation, rejecting ill-typed programs up front
+/// instead of surfacing a `TypeError` wherever the interpreter happens to
+/// reach the bad expression. Untyped scripts that the checker can't yet
+/// handle (e.g. ones relying on `+`'s runtime-only overload resolution)
+/// should keep using plain `eval`.
+pub fn eval_typechecked<'src>(
+    source: &'src str,
+    error_reporter: impl ErrorReporter,
+    parser: &mut Parser,
+    interpreter: &mut Interpreter,
+) -> Result<Value, EvalErrors<'src>> {
+    match parser.parse(source) {
+        Ok(stmts) => {
+            if let Err(err) = resolver::resolve(&stmts, parser.var_count()) {
+                let err = InterpreterError::from(err);
+                error_reporter.report(source, &errors::Error::new(None, err.clone()));
+                return Err(EvalErrors::Interpreter(vec![err]));
+            }
+
+            if let Err(errs) = typeck::Typechecker::new().check(&stmts) {
+                for err in errs.iter() {
+                    error_reporter.report(source, &errors::Error::new(None, err.clone()));
+                }
+
+                return Err(EvalErrors::Typecheck(errs));
+            }
+
+            match interpreter.interpret(&stmts, parser.var_count()) {
+                Ok(v) => Ok(v),
+                Err(errs) => {
+                    for err in errs.iter() {
+                        error_reporter.report(source, &errors::Error::new(None, err.clone()));
                     }
 
                     Err(EvalErrors::Interpreter(errs))
@@ -39,7 +110,51 @@ pub fn eval<'src>(
             }
         }
         Err(errs) => {
-            println!("parsing failed!");
+            for err in errs.iter() {
+                error_reporter.report(source, err);
+            }
+
+            Err(EvalErrors::Syntax(errs))
+        }
+    }
+}
+
+/// Like `eval`, but compiles the program to bytecode and runs it on the
+/// stack VM instead of walking the AST. The resolver still runs first, so
+/// a program the tree-walker rejects with a resolution error is rejected
+/// here too.
+///
+/// The VM backend doesn't support every construct the tree-walker does
+/// yet (closures, classes, arrays, property access, pipes — see
+/// `compiler::unsupported_feature`), so this rejects programs that use
+/// any of them up front rather than compiling them into a chunk the VM
+/// can only partially execute.
+pub fn eval_vm<'src>(
+    source: &'src str,
+    error_reporter: impl ErrorReporter,
+    parser: &mut Parser,
+    out: &mut InterpreterOutput,
+) -> Result<Value, EvalErrors<'src>> {
+    match parser.parse(source) {
+        Ok(stmts) => {
+            if let Err(err) = resolver::resolve(&stmts, parser.var_count()) {
+                let err = InterpreterError::from(err);
+                error_reporter.report(source, &errors::Error::new(None, err.clone()));
+                return Err(EvalErrors::Interpreter(vec![err]));
+            }
+
+            if let Some(feature) = compiler::unsupported_feature(&stmts) {
+                let err = InterpreterError::UnsupportedByVm(feature);
+                error_reporter.report(source, &errors::Error::new(None, err.clone()));
+                return Err(EvalErrors::Interpreter(vec![err]));
+            }
+
+            let chunk = Compiler::new().compile(&stmts);
+            let mut vm = Vm::new(&chunk, chunk.global_count, out);
+            vm.run()
+                .map_err(|err| EvalErrors::Interpreter(vec![err]))
+        }
+        Err(errs) => {
             for err in errs.iter() {
                 error_reporter.report(source, err);
             }
@@ -57,6 +172,8 @@ pub enum EvalErrors<'src> {
     Resolution(#[from] ResolverError),
     #[error("one or more runtime errors")]
     Interpreter(Vec<InterpreterError>),
+    #[error("one or more type errors")]
+    Typecheck(Vec<typeck::TypeError>),
 }
 
 impl<'src> EvalErrors<'src> {
@@ -83,4 +200,12 @@ impl<'src> EvalErrors<'src> {
             panic!()
         }
     }
+
+    pub fn unwrap_typecheck(self) -> Vec<typeck::TypeError> {
+        if let Self::Typecheck(err) = self {
+            err
+        } else {
+            panic!()
+        }
+    }
 }
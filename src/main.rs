@@ -1,18 +1,51 @@
 use std::{
-    io::{self, Write},
+    io,
     path::{Path, PathBuf},
     process::exit,
 };
 
 use clap::Parser as ClapParser;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
 
-use yarrlox::{interpreter::Interpreter, parser::Parser, EvalErrors};
+use yarrlox::{
+    interpreter::{Interpreter, InterpreterOutput},
+    parser::Parser,
+    Backend, EvalErrors,
+};
+
+/// Where the REPL's persistent line history lives across sessions.
+const HISTORY_FILE: &str = ".yarrlox_history";
 
 #[derive(ClapParser)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
     /// Script to run. If not provided, a REPL session is started
     script: Option<PathBuf>,
+
+    /// Execution backend: `tree-walk` (default) or `vm` for the bytecode VM
+    #[arg(long, value_enum, default_value_t = BackendArg::TreeWalk)]
+    backend: BackendArg,
+
+    /// Reject ill-typed programs before running them, via the optional
+    /// Hindley-Milner type-checking pass
+    #[arg(long)]
+    typecheck: bool,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum BackendArg {
+    TreeWalk,
+    Vm,
+}
+
+impl From<BackendArg> for Backend {
+    fn from(arg: BackendArg) -> Self {
+        match arg {
+            BackendArg::TreeWalk => Backend::TreeWalk,
+            BackendArg::Vm => Backend::Vm,
+        }
+    }
 }
 
 fn main() {
@@ -24,8 +57,10 @@ fn main() {
 fn run_cli() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    let backend = cli.backend.into();
+
     match cli.script {
-        Some(script) => run_script(script)?,
+        Some(script) => run_script(script, backend, cli.typecheck)?,
         None => run_repl()?,
     }
 
@@ -38,22 +73,43 @@ fn error_handler(err: anyhow::Error) {
     exit(42)
 }
 
-fn run_script(path: impl AsRef<Path>) -> anyhow::Result<()> {
+fn run_script(path: impl AsRef<Path>, backend: Backend, typecheck: bool) -> anyhow::Result<()> {
     let source = std::fs::read_to_string(path)?;
     let mut parser = Parser::new();
-    let mut interpreter = Interpreter::default();
-    match yarrlox::eval(
-        &source,
-        yarrlox::errors::SimpleReporter,
-        &mut parser,
-        &mut interpreter,
-    ) {
+
+    let result = match (backend, typecheck) {
+        (Backend::TreeWalk, true) => {
+            let mut interpreter = Interpreter::default();
+            yarrlox::eval_typechecked(
+                &source,
+                yarrlox::errors::SimpleReporter,
+                &mut parser,
+                &mut interpreter,
+            )
+        }
+        (Backend::TreeWalk, false) => {
+            let mut interpreter = Interpreter::default();
+            yarrlox::eval(
+                &source,
+                yarrlox::errors::SimpleReporter,
+                &mut parser,
+                &mut interpreter,
+            )
+        }
+        (Backend::Vm, _) => {
+            let mut out = InterpreterOutput::Stdout(io::stdout());
+            yarrlox::eval_vm(&source, yarrlox::errors::SimpleReporter, &mut parser, &mut out)
+        }
+    };
+
+    match result {
         Ok(_) => Ok(()),
         Err(EvalErrors::Syntax(_)) => Err(anyhow::anyhow!("syntax errors present")),
         Err(EvalErrors::Resolution(_)) => {
             Err(anyhow::anyhow!("variable resolution errors present"))
         }
         Err(EvalErrors::Interpreter(_)) => Err(anyhow::anyhow!("runtime errors present")),
+        Err(EvalErrors::Typecheck(_)) => Err(anyhow::anyhow!("type errors present")),
     }
 }
 
@@ -61,32 +117,65 @@ fn run_repl() -> anyhow::Result<()> {
     let mut parser = Parser::new();
     let mut interpreter = Interpreter::default();
 
-    fn prompt() -> std::io::Result<()> {
-        print!("> ");
-        io::stdout().flush()
-    }
+    let mut editor = DefaultEditor::new()?;
+    let _ = editor.load_history(HISTORY_FILE);
 
-    let stdin = io::stdin().lines();
+    let mut buffer = String::new();
 
-    prompt()?;
+    loop {
+        let prompt = if buffer.is_empty() { "> " } else { "... " };
 
-    for line in stdin {
-        match line {
+        match editor.readline(prompt) {
             Ok(line) => {
+                if !buffer.is_empty() {
+                    buffer.push('\n');
+                }
+                buffer.push_str(&line);
+
+                if input_is_incomplete(&buffer) {
+                    continue;
+                }
+
+                let _ = editor.add_history_entry(buffer.as_str());
                 let _ = yarrlox::eval(
-                    &line,
+                    &buffer,
                     yarrlox::errors::SimpleReporter,
                     &mut parser,
                     &mut interpreter,
                 );
+                buffer.clear();
+            }
+            Err(ReadlineError::Interrupted) => {
+                buffer.clear();
+                continue;
+            }
+            Err(ReadlineError::Eof) => break,
+            Err(e) => {
+                eprintln!("Error reading line: {}", e);
+                break;
             }
-            Err(e) => eprintln!("Error reading line: {}", e),
         }
-        prompt()?;
     }
 
+    let _ = editor.save_history(HISTORY_FILE);
+
     eprintln!();
     eprintln!("Buh-bye!");
 
     Ok(())
 }
+
+/// Decides whether `src` is a genuine prefix of a longer program (an
+/// unclosed `{`, a `fun` body that hasn't hit its closing brace yet) by
+/// re-parsing it in a scratch `Parser` and asking `parser::is_incomplete`
+/// whether the only errors produced were `UnexpectedEof`. A real syntax
+/// error (say, a stray `)`) still surfaces immediately instead of prompting
+/// forever. A scratch `Parser` is used rather than the REPL's persistent
+/// one so a partial, failed attempt doesn't advance its variable-id
+/// counter ahead of the eventual real parse of the completed buffer.
+fn input_is_incomplete(src: &str) -> bool {
+    match Parser::new().parse(src) {
+        Ok(_) => false,
+        Err(errs) => yarrlox::parser::is_incomplete(&errs),
+    }
+}
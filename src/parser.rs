@@ -3,12 +3,13 @@ use std::collections::VecDeque;
 use crate::ast::*;
 use crate::errors::Error;
 use crate::lexer::Lexer;
-use crate::token::{SpannedToken, Token};
+use crate::token::{LexicalError, SpannedToken, Token};
 
 #[derive(Default)]
 pub struct Parser {
     loop_depth: u32,
     next_var_expr_id: usize,
+    operators: OperatorTable,
 }
 
 impl<'src> Parser {
@@ -16,6 +17,17 @@ impl<'src> Parser {
         Self::default()
     }
 
+    /// Builds a parser with a caller-supplied [`OperatorTable`] in place of
+    /// the default one, so embedders can register custom infix operators
+    /// (or change precedence/associativity of existing ones) without
+    /// touching `parse_binop` itself.
+    pub fn with_operators(operators: OperatorTable) -> Self {
+        Self {
+            operators,
+            ..Self::default()
+        }
+    }
+
     pub fn var_count(&self) -> usize {
         self.next_var_expr_id
     }
@@ -68,6 +80,7 @@ impl<'src> Parser {
 
     fn parse_decl(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src, Stmt> {
         let res = match lexer.peek().unwrap() {
+            Token::Class => self.parse_class_decl(lexer)?,
             Token::Fun => self.parse_fun_decl(lexer)?,
             Token::Var => self.parse_var_decl(lexer)?,
             _ => self.parse_stmt(lexer)?,
@@ -76,9 +89,46 @@ impl<'src> Parser {
         Ok(res)
     }
 
+    fn parse_class_decl(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src, Stmt> {
+        lexer.next().unwrap();
+
+        let name = self.parse_ident(lexer)?;
+
+        let superclass = if self.expect(lexer, Token::Less).is_ok() {
+            let ident = self.parse_ident(lexer)?;
+            Some(self.make_reference(ident))
+        } else {
+            None
+        };
+
+        self.expect(lexer, Token::LeftBrace)?;
+
+        let mut methods = vec![];
+        while !matches!(lexer.peek(), Some(Token::RightBrace) | None) {
+            methods.push(self.parse_function(lexer)?);
+        }
+
+        lexer
+            .next()
+            .ok_or_else(|| Error::new(None, ParserErrorKind::UnexpectedEof))?;
+
+        Ok(Stmt::Class(Class {
+            name,
+            superclass,
+            methods,
+        }))
+    }
+
     fn parse_fun_decl(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src, Stmt> {
         lexer.next().unwrap();
 
+        Ok(Stmt::Function(self.parse_function(lexer)?))
+    }
+
+    /// Parses a function's name/params/body — the part a `fun` declaration
+    /// and a class method share, since a method is just a function without
+    /// the leading `fun` keyword.
+    fn parse_function(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src, Function> {
         let name = self.parse_ident(lexer)?;
 
         self.expect(lexer, Token::LeftParen)?;
@@ -102,7 +152,7 @@ impl<'src> Parser {
 
         let body = self.parse_block(lexer)?;
 
-        Ok(Stmt::Function(Function { name, params, body }))
+        Ok(Function { name, params, body })
     }
 
     fn parse_ident(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src, String> {
@@ -307,16 +357,43 @@ impl<'src> Parser {
     fn parse_assignment(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src> {
         let expr = self.parse_binop(lexer, 0)?;
 
+        if self.expect(lexer, Token::Question).is_ok() {
+            let then_branch = self.parse_assignment(lexer)?;
+            self.expect(lexer, Token::Colon)?;
+            let else_branch = self.parse_assignment(lexer)?;
+
+            return Ok(Expr::Ternary(Ternary {
+                cond: Box::new(expr),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            }));
+        }
+
         if let Ok(equals) = self.expect(lexer, Token::Equal) {
             let value = self.parse_assignment(lexer)?;
 
-            if let Expr::Literal(Literal::Identifier(name)) = expr {
-                return Ok(Expr::Assign(Assign {
-                    name,
-                    value: Box::new(value),
-                }));
-            } else {
-                return Err(Error::new(equals, ParserErrorKind::InvalidLvalue));
+            match expr {
+                Expr::Literal(Literal::Identifier(name)) => {
+                    return Ok(Expr::Assign(Assign {
+                        name,
+                        value: Box::new(value),
+                    }))
+                }
+                Expr::Get(Get { object, name }) => {
+                    return Ok(Expr::Set(Set {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    }))
+                }
+                Expr::Index(Index { object, index, .. }) => {
+                    return Ok(Expr::IndexSet(IndexSet {
+                        object,
+                        index,
+                        value: Box::new(value),
+                    }))
+                }
+                _ => return Err(Error::new(equals, ParserErrorKind::InvalidLvalue)),
             }
         }
 
@@ -329,9 +406,7 @@ impl<'src> Parser {
     fn parse_binop(&mut self, lexer: &mut Lexer<'src>, min_prec: u32) -> ParseResult<'src> {
         let mut expr = self.parse_unary(lexer)?;
 
-        while let Some(op) = self.peek_binary_operator(lexer) {
-            let (prec, assoc) = op.prec_assoc();
-
+        while let Some((op, prec, assoc)) = self.peek_binary_operator(lexer) {
             if prec < min_prec {
                 break;
             }
@@ -383,6 +458,14 @@ impl<'src> Parser {
         loop {
             if self.expect(lexer, Token::LeftParen).is_ok() {
                 expr = self.finish_call(lexer, expr)?;
+            } else if self.expect(lexer, Token::Dot).is_ok() {
+                let name = self.parse_ident(lexer)?;
+                expr = Expr::Get(Get {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else if let Ok(bracket) = self.expect(lexer, Token::LeftBracket) {
+                expr = self.finish_index(lexer, expr, bracket)?;
             } else {
                 break;
             }
@@ -391,6 +474,29 @@ impl<'src> Parser {
         Ok(expr)
     }
 
+    fn finish_index(
+        &mut self,
+        lexer: &mut Lexer<'src>,
+        object: Expr,
+        bracket_start: SpannedToken<'src>,
+    ) -> ParseResult<'src> {
+        if lexer.peek() == Some(Token::RightBracket) {
+            return Err(Error::new(bracket_start, ParserErrorKind::MalformedIndexExpr));
+        }
+
+        let index = self.parse_expr(lexer)?;
+
+        let bracket_end = self
+            .expect(lexer, Token::RightBracket)
+            .map_err(|_| Error::new(bracket_start.clone(), ParserErrorKind::MalformedIndexExpr))?;
+
+        Ok(Expr::Index(Index {
+            object: Box::new(object),
+            index: Box::new(index),
+            bracket_span: bracket_start.span.start..bracket_end.span.end,
+        }))
+    }
+
     fn finish_call(&mut self, lexer: &mut Lexer<'src>, callee: Expr) -> ParseResult<'src> {
         let mut args = vec![];
         if lexer
@@ -425,26 +531,70 @@ impl<'src> Parser {
             .next()
             .ok_or_else(|| ParserError::new(None, ParserErrorKind::UnexpectedEof))?;
         Ok(match token.token {
-            Token::NumLit(l) => Expr::Literal(Literal::NumLit(NumLit(l.to_string()))),
-            Token::StringLit(l) => Expr::Literal(Literal::StringLit(StringLit(l.to_string()))),
+            Token::NumLit(l) => Expr::Literal(Literal::NumLit(NumLit(l))),
+            Token::StringLit(l) => Expr::Literal(Literal::StringLit(StringLit(l))),
             Token::Identifier(l) => self.make_var_expr(l),
             Token::Nil => Expr::Literal(Literal::Nil),
             Token::True => Expr::Literal(Literal::Bool(true)),
             Token::False => Expr::Literal(Literal::Bool(false)),
+            Token::This => self.make_var_expr("this"),
+            // `super` always appears as `super.method`; it parses to the
+            // same synthetic-identifier expression `this` does, so the
+            // `.method` part falls out of `parse_call`'s existing `Dot`
+            // handling for free.
+            Token::Super => self.make_var_expr("super"),
             Token::LeftParen => self.parse_paren_expr(lexer)?,
+            Token::LeftBracket => self.parse_array_lit(lexer)?,
+            Token::UnterminatedString => {
+                Err(Error::new(token, ParserErrorKind::Lexical(LexicalError::UnterminatedString)))?
+            }
+            Token::MalformedEscapeSequence => Err(Error::new(
+                token,
+                ParserErrorKind::Lexical(LexicalError::MalformedEscapeSequence),
+            ))?,
+            Token::MalformedNumber => {
+                Err(Error::new(token, ParserErrorKind::Lexical(LexicalError::MalformedNumber)))?
+            }
             _ => Err(Error::new(token, ParserErrorKind::UnexpectedToken))?,
         })
     }
 
     fn make_var_expr(&mut self, ident: &str) -> Expr {
-        let expr = Expr::Literal(Literal::Identifier(Reference {
+        Expr::Literal(Literal::Identifier(self.make_reference(ident.to_string())))
+    }
+
+    fn make_reference(&mut self, ident: String) -> Reference {
+        let reference = Reference {
             id: self.next_var_expr_id,
-            ident: ident.to_string(),
-        }));
+            ident,
+        };
 
         self.next_var_expr_id += 1;
 
-        expr
+        reference
+    }
+
+    /// Parses `[a, b, c]`, mirroring `finish_call`'s comma-separated loop
+    /// (255-element guard included) since an array literal is really just
+    /// a call's argument list without the callee.
+    fn parse_array_lit(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src> {
+        let mut elements = vec![];
+        if lexer.peek() != Some(Token::RightBracket) {
+            while {
+                if elements.len() >= 255 {
+                    return Err(Error::new(
+                        lexer.peek_spanned(),
+                        ParserErrorKind::TooManyArgs,
+                    ));
+                }
+                elements.push(self.parse_expr(lexer)?);
+                self.expect(lexer, Token::Comma).is_ok()
+            } {}
+        }
+
+        self.expect(lexer, Token::RightBracket)?;
+
+        Ok(Expr::ArrayLit(ArrayLit { elements }))
     }
 
     fn parse_paren_expr(&mut self, lexer: &mut Lexer<'src>) -> ParseResult<'src> {
@@ -493,22 +643,8 @@ impl<'src> Parser {
         }
     }
 
-    fn peek_binary_operator(&mut self, lexer: &mut Lexer<'src>) -> Option<BinaryOp> {
-        match lexer.peek() {
-            Some(Token::Plus) => Some(BinaryOp::Add),
-            Some(Token::Minus) => Some(BinaryOp::Sub),
-            Some(Token::Star) => Some(BinaryOp::Mul),
-            Some(Token::Slash) => Some(BinaryOp::Div),
-            Some(Token::Greater) => Some(BinaryOp::Gt),
-            Some(Token::GreaterEqual) => Some(BinaryOp::Gte),
-            Some(Token::Less) => Some(BinaryOp::Lt),
-            Some(Token::LessEqual) => Some(BinaryOp::Lte),
-            Some(Token::EqualEqual) => Some(BinaryOp::Eq),
-            Some(Token::BangEqual) => Some(BinaryOp::NotEq),
-            Some(Token::Or) => Some(BinaryOp::LogicOr),
-            Some(Token::And) => Some(BinaryOp::LogicAnd),
-            _ => None,
-        }
+    fn peek_binary_operator(&mut self, lexer: &mut Lexer<'src>) -> Option<(BinaryOp, u32, Assoc)> {
+        self.operators.lookup(&lexer.peek()?)
     }
 
     fn peek_unary_operator(&mut self, lexer: &mut Lexer<'src>) -> Option<UnaryOp> {
@@ -520,31 +656,75 @@ impl<'src> Parser {
     }
 }
 
-#[allow(unused)]
-enum Assoc {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Assoc {
     Left,
     Right,
 }
 
-trait Precedence {
-    fn prec_assoc(&self) -> (u32, Assoc);
+/// A data-driven table of infix operators, mapping the [`Token`] that
+/// introduces one to the [`BinaryOp`] it produces plus its precedence and
+/// associativity. `Parser::new` starts from [`OperatorTable::default`];
+/// `Parser::with_operators` lets a caller swap in a table that adds,
+/// removes, or reprioritizes entries without `parse_binop`'s precedence
+/// climbing itself ever changing.
+///
+/// Entries are keyed by `Token<'static>` rather than a plain string: every
+/// operator token is a unit variant, and Rust's lifetime covariance lets a
+/// `Token<'static>` stand in for a `Token<'src>` peeked off the live lexer
+/// when comparing the two with `==`.
+#[derive(Clone, Debug)]
+pub struct OperatorTable {
+    operators: Vec<(Token<'static>, BinaryOp, u32, Assoc)>,
 }
 
-impl Precedence for BinaryOp {
-    fn prec_assoc(&self) -> (u32, Assoc) {
-        match self {
-            BinaryOp::LogicOr => (0, Assoc::Left),
-            BinaryOp::LogicAnd => (1, Assoc::Left),
-            BinaryOp::Eq => (2, Assoc::Left),
-            BinaryOp::NotEq => (2, Assoc::Left),
-            BinaryOp::Gt => (3, Assoc::Left),
-            BinaryOp::Lt => (3, Assoc::Left),
-            BinaryOp::Gte => (3, Assoc::Left),
-            BinaryOp::Lte => (3, Assoc::Left),
-            BinaryOp::Add => (4, Assoc::Left),
-            BinaryOp::Sub => (4, Assoc::Left),
-            BinaryOp::Mul => (5, Assoc::Left),
-            BinaryOp::Div => (5, Assoc::Left),
+impl OperatorTable {
+    /// Registers (or, if `token` is already bound, overrides) an infix
+    /// operator. Returns `self` so entries can be chained before the table
+    /// is handed to `Parser::with_operators`.
+    pub fn with_operator(
+        mut self,
+        token: Token<'static>,
+        op: BinaryOp,
+        prec: u32,
+        assoc: Assoc,
+    ) -> Self {
+        self.operators.retain(|(t, ..)| *t != token);
+        self.operators.push((token, op, prec, assoc));
+        self
+    }
+
+    fn lookup(&self, token: &Token<'_>) -> Option<(BinaryOp, u32, Assoc)> {
+        self.operators
+            .iter()
+            .find(|(t, ..)| t == token)
+            .map(|(_, op, prec, assoc)| (op.clone(), *prec, *assoc))
+    }
+}
+
+impl Default for OperatorTable {
+    fn default() -> Self {
+        use Assoc::*;
+        use BinaryOp::*;
+
+        Self {
+            operators: vec![
+                (Token::PipeForward, PipeForward, 0, Left),
+                (Token::PipeMap, PipeMap, 0, Left),
+                (Token::Or, LogicOr, 1, Left),
+                (Token::And, LogicAnd, 2, Left),
+                (Token::EqualEqual, Eq, 3, Left),
+                (Token::BangEqual, NotEq, 3, Left),
+                (Token::Greater, Gt, 4, Left),
+                (Token::Less, Lt, 4, Left),
+                (Token::GreaterEqual, Gte, 4, Left),
+                (Token::LessEqual, Lte, 4, Left),
+                (Token::Plus, Add, 5, Left),
+                (Token::Minus, Sub, 5, Left),
+                (Token::Star, Mul, 6, Left),
+                (Token::Slash, Div, 6, Left),
+                (Token::Percent, Mod, 6, Left),
+            ],
         }
     }
 }
@@ -553,6 +733,18 @@ pub type ParseResult<'src, T = Expr> = Result<T, ParserError<'src>>;
 
 pub type ParserError<'src> = Error<'src, ParserErrorKind>;
 
+/// Whether a failed parse reflects genuinely incomplete input — it ran out
+/// of source while a rule still expected more tokens — rather than a real
+/// syntax error. A REPL can use this to keep reading further lines instead
+/// of surfacing the error on the first one; a stray token (`UnexpectedToken`
+/// and friends) still fails immediately since more input wouldn't fix it.
+pub fn is_incomplete(errors: &[ParserError]) -> bool {
+    !errors.is_empty()
+        && errors
+            .iter()
+            .all(|err| *err.error_kind() == ParserErrorKind::UnexpectedEof)
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, thiserror::Error)]
 pub enum ParserErrorKind {
     #[error("no rule expected token")]
@@ -565,6 +757,10 @@ pub enum ParserErrorKind {
     BreakOutsideLoop,
     #[error("a function call can only accept up to 255 args")]
     TooManyArgs,
+    #[error("{0}")]
+    Lexical(#[from] LexicalError),
+    #[error("empty or unterminated index expression")]
+    MalformedIndexExpr,
 }
 
 #[cfg(test)]
@@ -617,4 +813,61 @@ mod tests {
 
         assert_eq!(expr, expected);
     }
+
+    #[test]
+    fn parse_modulo_same_precedence_as_mul_div() {
+        let mut lexer = Lexer::new("8 % 3 * 2");
+        let expr = Parser::new().parse_expr(&mut lexer).unwrap();
+        dbg!(&expr);
+
+        let expected = Expr::Binary(Binary {
+            left: Box::new(Expr::Binary(Binary {
+                left: Box::new(Expr::Literal(Literal::NumLit(NumLit("8".to_string())))),
+                right: Box::new(Expr::Literal(Literal::NumLit(NumLit("3".to_string())))),
+                op: BinaryOp::Mod,
+            })),
+            right: Box::new(Expr::Literal(Literal::NumLit(NumLit("2".to_string())))),
+            op: BinaryOp::Mul,
+        });
+
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn parse_ternary_is_right_associative() {
+        let mut lexer = Lexer::new("true ? 1 : false ? 2 : 3");
+        let expr = Parser::new().parse_expr(&mut lexer).unwrap();
+        dbg!(&expr);
+
+        let expected = Expr::Ternary(Ternary {
+            cond: Box::new(Expr::Literal(Literal::Bool(true))),
+            then_branch: Box::new(Expr::Literal(Literal::NumLit(NumLit("1".to_string())))),
+            else_branch: Box::new(Expr::Ternary(Ternary {
+                cond: Box::new(Expr::Literal(Literal::Bool(false))),
+                then_branch: Box::new(Expr::Literal(Literal::NumLit(NumLit("2".to_string())))),
+                else_branch: Box::new(Expr::Literal(Literal::NumLit(NumLit("3".to_string())))),
+            })),
+        });
+
+        assert_eq!(expr, expected);
+    }
+
+    #[test]
+    fn with_operators_overrides_default_table() {
+        let operators =
+            OperatorTable::default().with_operator(Token::Percent, BinaryOp::Add, 6, Assoc::Left);
+        let mut lexer = Lexer::new("1 % 2");
+        let expr = Parser::with_operators(operators)
+            .parse_expr(&mut lexer)
+            .unwrap();
+        dbg!(&expr);
+
+        let expected = Expr::Binary(Binary {
+            left: Box::new(Expr::Literal(Literal::NumLit(NumLit("1".to_string())))),
+            right: Box::new(Expr::Literal(Literal::NumLit(NumLit("2".to_string())))),
+            op: BinaryOp::Add,
+        });
+
+        assert_eq!(expr, expected);
+    }
 }
@@ -49,6 +49,11 @@ impl<'ast> Resolver<'ast> {
                 self.resolve(stmts)?;
                 self.end_scope();
             }
+            Stmt::Class(Class {
+                name,
+                superclass,
+                methods,
+            }) => self.resolve_class_decl(name, superclass.as_ref(), methods)?,
             Stmt::Expr(expr) => self.resolve_expr(expr)?,
             Stmt::Function(fun) => self.resolve_fun_decl(fun)?,
             Stmt::If {
@@ -87,6 +92,46 @@ impl<'ast> Resolver<'ast> {
         Ok(())
     }
 
+    /// A class's own name is declared in the enclosing scope, same as a
+    /// function. Each method is then resolved with its own `this` scope
+    /// (and, when there's a superclass, a `super` scope wrapping that),
+    /// so `Interpreter::bind_method` can build an env chain whose shape
+    /// matches the distances computed here exactly.
+    fn resolve_class_decl(
+        &mut self,
+        name: &'ast str,
+        superclass: Option<&'ast Reference>,
+        methods: &'ast [Function],
+    ) -> ResolverResult {
+        self.declare(name)?;
+        self.define(name);
+
+        if let Some(superclass) = superclass {
+            if superclass.ident == name {
+                return Err(ResolverError::SelfInherit);
+            }
+            self.resolve_local(superclass);
+
+            self.begin_scope();
+            self.scopes.get_mut(0).unwrap().insert("super", true);
+        }
+
+        self.begin_scope();
+        self.scopes.get_mut(0).unwrap().insert("this", true);
+
+        for method in methods {
+            self.resolve_fun(method)?;
+        }
+
+        self.end_scope();
+
+        if superclass.is_some() {
+            self.end_scope();
+        }
+
+        Ok(())
+    }
+
     fn resolve_fun(&mut self, fun: &'ast Function) -> ResolverResult {
         self.begin_scope();
         for param in &fun.params {
@@ -119,6 +164,34 @@ impl<'ast> Resolver<'ast> {
                     self.resolve_expr(arg)?;
                 }
             }
+            Expr::Get(Get { object, .. }) => self.resolve_expr(object)?,
+            Expr::Set(Set { object, value, .. }) => {
+                self.resolve_expr(value)?;
+                self.resolve_expr(object)?;
+            }
+            Expr::ArrayLit(ArrayLit { elements }) => {
+                for element in elements {
+                    self.resolve_expr(element)?;
+                }
+            }
+            Expr::Index(Index { object, index, .. }) => {
+                self.resolve_expr(object)?;
+                self.resolve_expr(index)?;
+            }
+            Expr::IndexSet(IndexSet { object, index, value }) => {
+                self.resolve_expr(value)?;
+                self.resolve_expr(object)?;
+                self.resolve_expr(index)?;
+            }
+            Expr::Ternary(Ternary {
+                cond,
+                then_branch,
+                else_branch,
+            }) => {
+                self.resolve_expr(cond)?;
+                self.resolve_expr(then_branch)?;
+                self.resolve_expr(else_branch)?;
+            }
         }
 
         Ok(())
@@ -184,10 +257,12 @@ impl<'ast> Resolver<'ast> {
 
 type ResolverResult = Result<(), ResolverError>;
 
-#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
 pub enum ResolverError {
     #[error("Can't read local variable in its own initializer.")]
     SelfInitialize,
     #[error("Variable `{0}` defined more than once in the same scope")]
     MultipleDeclaration(String),
+    #[error("A class can't inherit from itself.")]
+    SelfInherit,
 }
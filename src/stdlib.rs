@@ -0,0 +1,756 @@
+//! Native functions beyond `clock`. `load` registers the default set into
+//! an `Env`; embedders who want a smaller (or different) surface can skip
+//! `load` and `Env::define` their own `Value::Callable`s instead, the same
+//! way `make_global_env` wires up `Clock` in `interpreter.rs`.
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::io::BufRead;
+use std::rc::Rc;
+
+use crate::callable::{self, Arity, Callable, FnId};
+use crate::env::Env;
+use crate::interpreter::{Interpreter, InterpreterError};
+use crate::value::{Type, Value};
+
+/// Registers the standard builtins into `env`, assigning each one a fresh
+/// [`FnId`] out of `fn_registry`. Called by `interpreter::make_global_env`
+/// in addition to `clock`.
+pub fn load(env: &Rc<RefCell<Env>>, fn_registry: &mut Vec<Box<dyn Callable>>) {
+    let mut env = env.borrow_mut();
+    env.define(
+        "input",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Input::new))),
+    );
+    env.define(
+        "str",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Str::new))),
+    );
+    env.define(
+        "num",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, NumOf::new))),
+    );
+    env.define(
+        "len",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Len::new))),
+    );
+    env.define(
+        "sqrt",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Sqrt::new))),
+    );
+    env.define(
+        "floor",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Floor::new))),
+    );
+    env.define(
+        "abs",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Abs::new))),
+    );
+    env.define(
+        "typeof",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, TypeOf::new))),
+    );
+    env.define(
+        "random",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Random::new))),
+    );
+    env.define(
+        "randomInt",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            RandomInt::new,
+        ))),
+    );
+    env.define(
+        "randomBool",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            RandomBool::new,
+        ))),
+    );
+    env.define(
+        "seedRandom",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            SeedRandom::new,
+        ))),
+    );
+    env.define(
+        "pow",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Pow::new))),
+    );
+    env.define(
+        "ceil",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Ceil::new))),
+    );
+    env.define(
+        "log",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Log::new))),
+    );
+    env.define(
+        "min",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Min::new))),
+    );
+    env.define(
+        "max",
+        Value::Callable(Rc::new(callable::register_callable(fn_registry, Max::new))),
+    );
+    env.define(
+        "readFile",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            ReadFile::new,
+        ))),
+    );
+    env.define(
+        "writeFile",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            WriteFile::new,
+        ))),
+    );
+    env.define(
+        "appendFile",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            AppendFile::new,
+        ))),
+    );
+    env.define(
+        "tempFile",
+        Value::Callable(Rc::new(callable::register_callable(
+            fn_registry,
+            TempFile::new,
+        ))),
+    );
+}
+
+macro_rules! native_fn {
+    ($name:ident) => {
+        #[derive(Debug, Clone)]
+        struct $name {
+            id: FnId,
+        }
+
+        impl $name {
+            fn new(id: FnId) -> Self {
+                Self { id }
+            }
+
+            fn boxed(&self) -> Box<dyn Callable> {
+                Box::new(self.clone())
+            }
+        }
+    };
+}
+
+native_fn!(Input);
+impl Callable for Input {
+    fn call(&self, _: &mut Interpreter, _args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) => Ok(Value::Nil), // EOF
+            Ok(_) => {
+                if line.ends_with('\n') {
+                    line.pop();
+                    if line.ends_with('\r') {
+                        line.pop();
+                    }
+                }
+                Ok(Value::string(line))
+            }
+            Err(_) => Ok(Value::Nil),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(0)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(Str);
+impl Callable for Str {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        Ok(Value::string(format!("{}", display_unquoted(&args[0]))))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(NumOf);
+impl Callable for NumOf {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        match &args[0] {
+            Value::Num(n) => Ok(Value::Num(*n)),
+            Value::String(s) => s
+                .trim()
+                .parse()
+                .map(Value::Num)
+                .map_err(|_| InterpreterError::TypeError {
+                    expected: &[Type::Num],
+                    found: Type::String,
+                }),
+            v => Err(InterpreterError::TypeError {
+                expected: &[Type::Num, Type::String],
+                found: v.ty(),
+            }),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(Len);
+impl Callable for Len {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        match &args[0] {
+            Value::String(s) => Ok(Value::Num(s.chars().count() as f64)),
+            v => Err(InterpreterError::TypeError {
+                expected: &[Type::String],
+                found: v.ty(),
+            }),
+        }
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+macro_rules! math_fn {
+    ($name:ident, $method:ident) => {
+        native_fn!($name);
+        impl Callable for $name {
+            fn call(
+                &self,
+                _: &mut Interpreter,
+                args: Vec<Value>,
+            ) -> Result<Value, InterpreterError> {
+                Ok(Value::Num(expect_num(&args[0])?.$method()))
+            }
+
+            fn arity(&self) -> Arity {
+                Arity::Exact(1)
+            }
+
+            fn id(&self) -> FnId {
+                self.id
+            }
+
+            fn boxed_clone(&self) -> Box<dyn Callable> {
+                self.boxed()
+            }
+
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        }
+    };
+}
+
+math_fn!(Sqrt, sqrt);
+math_fn!(Floor, floor);
+math_fn!(Abs, abs);
+math_fn!(Ceil, ceil);
+math_fn!(Log, ln);
+
+native_fn!(TypeOf);
+impl Callable for TypeOf {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        Ok(Value::string(args[0].ty().to_string()))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// -- Randomness --
+//
+// A self-contained SplitMix64 generator (see `Interpreter::next_rng_u64`)
+// backs all four of these, so there's no dependency on an external `rand`
+// crate. The state lives on `Interpreter` rather than these structs, since
+// every call must observe the *same* stream regardless of which one of
+// `random`/`randomInt`/`randomBool` last advanced it.
+
+fn expect_int(v: &Value) -> Result<i64, InterpreterError> {
+    match v {
+        Value::Int(i) => Ok(*i),
+        v => Err(InterpreterError::TypeError {
+            expected: &[Type::Int],
+            found: v.ty(),
+        }),
+    }
+}
+
+fn expect_string(v: &Value) -> Result<&str, InterpreterError> {
+    match v {
+        Value::String(s) => Ok(s),
+        v => Err(InterpreterError::TypeError {
+            expected: &[Type::String],
+            found: v.ty(),
+        }),
+    }
+}
+
+/// Widens any of the numeric tower's three variants to `f64`, the same
+/// coercion `interpreter::numeric_compare` applies for comparisons — these
+/// natives don't need to stay exact either.
+fn expect_num(v: &Value) -> Result<f64, InterpreterError> {
+    match v {
+        Value::Int(i) => Ok(*i as f64),
+        Value::Rational(r) => Ok(r.to_f64()),
+        Value::Num(n) => Ok(*n),
+        v => Err(InterpreterError::TypeError {
+            expected: &[Type::Num, Type::Int, Type::Rational],
+            found: v.ty(),
+        }),
+    }
+}
+
+native_fn!(Random);
+impl Callable for Random {
+    fn call(&self, interpreter: &mut Interpreter, _args: Vec<Value>) -> Result<Value, InterpreterError> {
+        Ok(Value::Num(interpreter.next_rng_f64()))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(0)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// An integer in the half-open range `[lo, hi)`, drawn by scaling a uniform
+// `random()` draw rather than taking `next_rng_u64() % span` so it doesn't
+// carry that approach's modulo bias. `hi <= lo` is treated as an empty
+// range collapsed to its single boundary, `lo`, rather than an error.
+native_fn!(RandomInt);
+impl Callable for RandomInt {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let lo = expect_int(&args[0])?;
+        let hi = expect_int(&args[1])?;
+
+        if hi <= lo {
+            return Ok(Value::Int(lo));
+        }
+
+        let span = (hi - lo) as f64;
+        Ok(Value::Int(lo + (interpreter.next_rng_f64() * span) as i64))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(2)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// `true` with probability `p`. `p <= 0.0` and `p >= 1.0` are handled as
+// guaranteed false/true rather than left to a `next_rng_f64() < p`
+// comparison, so a "1-in-1 chance" can't occasionally come back false due
+// to float rounding.
+native_fn!(RandomBool);
+impl Callable for RandomBool {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let p = expect_num(&args[0])?;
+
+        let result = if p <= 0.0 {
+            false
+        } else if p >= 1.0 {
+            true
+        } else {
+            interpreter.next_rng_f64() < p
+        };
+
+        Ok(Value::Bool(result))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(SeedRandom);
+impl Callable for SeedRandom {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        let seed = expect_int(&args[0])?;
+        interpreter.seed_rng(seed as u64);
+        Ok(Value::Nil)
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// -- Domain-aware math --
+
+/// `base.checked_pow(exp)`-equivalent for `i64`, via the standard
+/// repeated-squaring recurrence, so overflow is detected exactly instead of
+/// silently wrapping or jumping to a lossy `f64`. `exp` is assumed `>= 0`;
+/// negative exponents are handled by `Pow::call` falling back to `powf`
+/// before this is ever reached.
+fn checked_ipow(mut base: i64, mut exp: u64) -> Option<i64> {
+    let mut acc: i64 = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            acc = acc.checked_mul(base)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(acc)
+}
+
+// `base ** exp`. When both arguments are `Int` and `exp` is non-negative,
+// computes the exact `i64` result with `checked_ipow`, erroring on
+// overflow rather than silently promoting to a lossy `f64::powf`; any
+// other combination (a `Num`/`Rational` operand, or a negative exponent)
+// falls back to `f64::powf` directly.
+native_fn!(Pow);
+impl Callable for Pow {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        if let (Value::Int(base), Value::Int(exp)) = (&args[0], &args[1]) {
+            if *exp >= 0 {
+                return checked_ipow(*base, *exp as u64)
+                    .map(Value::Int)
+                    .ok_or(InterpreterError::NumericOverflow);
+            }
+        }
+
+        let base = expect_num(&args[0])?;
+        let exp = expect_num(&args[1])?;
+        Ok(Value::Num(base.powf(exp)))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(2)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(Min);
+impl Callable for Min {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        args.iter()
+            .map(expect_num)
+            .try_fold(f64::INFINITY, |acc, n| n.map(|n| acc.min(n)))
+            .map(Value::Num)
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Range(1, None)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(Max);
+impl Callable for Max {
+    fn call(&self, _: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        args.iter()
+            .map(expect_num)
+            .try_fold(f64::NEG_INFINITY, |acc, n| n.map(|n| acc.max(n)))
+            .map(Value::Num)
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Range(1, None)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// -- File I/O --
+//
+// Gated behind `Interpreter::allow_io` (see `Interpreter::with_allow_io`):
+// an embedder running untrusted Lox can build with `allow_io: false` and
+// have every native in this section error out instead of touching the
+// filesystem, while still getting `Clock`/math/random.
+
+native_fn!(ReadFile);
+impl Callable for ReadFile {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        if !interpreter.allow_io() {
+            return Err(InterpreterError::IoDisabled);
+        }
+
+        let path = expect_string(&args[0])?;
+        std::fs::read_to_string(path)
+            .map(Value::string)
+            .map_err(|e| InterpreterError::Io(e.to_string()))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(1)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(WriteFile);
+impl Callable for WriteFile {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        if !interpreter.allow_io() {
+            return Err(InterpreterError::IoDisabled);
+        }
+
+        let path = expect_string(&args[0])?;
+        let contents = expect_string(&args[1])?;
+        std::fs::write(path, contents)
+            .map(|_| Value::Nil)
+            .map_err(|e| InterpreterError::Io(e.to_string()))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(2)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+native_fn!(AppendFile);
+impl Callable for AppendFile {
+    fn call(&self, interpreter: &mut Interpreter, args: Vec<Value>) -> Result<Value, InterpreterError> {
+        if !interpreter.allow_io() {
+            return Err(InterpreterError::IoDisabled);
+        }
+
+        let path = expect_string(&args[0])?;
+        let contents = expect_string(&args[1])?;
+
+        (|| -> std::io::Result<()> {
+            use std::io::Write;
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?
+                .write_all(contents.as_bytes())
+        })()
+        .map(|_| Value::Nil)
+        .map_err(|e| InterpreterError::Io(e.to_string()))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(2)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Creates a uniquely named scratch file in the system temp directory and
+// hands its path back as a string. The name is derived from the shared
+// `Interpreter` RNG rather than a process-global counter, since that's
+// already the interpreter's source of unique values; the interpreter
+// tracks the path (`Interpreter::track_temp_file`) and deletes it on
+// `Drop`, so callers don't need their own cleanup.
+native_fn!(TempFile);
+impl Callable for TempFile {
+    fn call(&self, interpreter: &mut Interpreter, _args: Vec<Value>) -> Result<Value, InterpreterError> {
+        if !interpreter.allow_io() {
+            return Err(InterpreterError::IoDisabled);
+        }
+
+        let path = std::env::temp_dir().join(format!("yarrlox-{:016x}.tmp", interpreter.next_rng_u64()));
+        std::fs::File::create(&path).map_err(|e| InterpreterError::Io(e.to_string()))?;
+
+        let path_string = path.to_string_lossy().into_owned();
+        interpreter.track_temp_file(path);
+        Ok(Value::string(path_string))
+    }
+
+    fn arity(&self) -> Arity {
+        Arity::Exact(0)
+    }
+
+    fn id(&self) -> FnId {
+        self.id
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Callable> {
+        self.boxed()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn display_unquoted(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
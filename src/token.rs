@@ -1,14 +1,43 @@
+use std::fmt::Display;
 use std::ops::Range;
 
 use logos::{Filter, Logos};
 
+/// A 1-based line/column position, as computed by `Lexer`'s newline table
+/// (see `lexer::LineIndex`). Columns count `char`s, not bytes, so multi-byte
+/// UTF-8 doesn't throw off alignment with the source text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Display for Position {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SpannedToken<'src> {
     pub token: Token<'src>,
     pub span: Range<usize>,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl<'src> SpannedToken<'src> {
+    /// The token's starting position, for diagnostics that only need a
+    /// single point to point at (e.g. a caret under the first character).
+    pub fn position(&self) -> Position {
+        self.start
+    }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Logos)]
+// `StringLit` and `NumLit` now own their decoded/normalized `String`s, so
+// `Token` can no longer be `Copy` the way every other variant (plain tokens
+// and `&'src str` slices) would allow.
+#[derive(Clone, Debug, PartialEq, Eq, Logos)]
 pub enum Token<'src> {
     // Single-character tokens.
     #[token("(")]
@@ -19,6 +48,10 @@ pub enum Token<'src> {
     LeftBrace,
     #[token("}")]
     RightBrace,
+    #[token("[")]
+    LeftBracket,
+    #[token("]")]
+    RightBracket,
     #[token(",")]
     Comma,
     #[token(".")]
@@ -33,6 +66,12 @@ pub enum Token<'src> {
     Slash,
     #[token("*")]
     Star,
+    #[token("%")]
+    Percent,
+    #[token("?")]
+    Question,
+    #[token(":")]
+    Colon,
 
     // One or two character tokens.
     #[token("!")]
@@ -51,18 +90,52 @@ pub enum Token<'src> {
     Less,
     #[token("<=")]
     LessEqual,
+    #[token("|>")]
+    PipeForward,
+    #[token("|:")]
+    PipeMap,
 
     // Literals.
     #[regex("[a-zA-Z_][a-zA-Z0-9_]*")]
     Identifier(&'src str),
-    #[regex(r#""([^"\\]|\\.)*""#, callback = trim_string)]
-    StringLit(&'src str),
-    #[regex(r#"[0-9]+(\.[0-9]+)?"#)]
-    NumLit(&'src str),
+    // Only the handful of escapes `decode_string` understands are allowed
+    // here, so a well-formed match always decodes cleanly. A quoted span
+    // containing some OTHER escape still closes, so it falls through to
+    // the wider `MalformedEscapeSequence` pattern below instead of this
+    // one; an unterminated quote matches neither and falls through further
+    // still, to the single `"` token.
+    #[regex(r#""([^"\\]|\\[nrt"\\]|\\u\{[0-9a-fA-F]+\})*""#, callback = decode_string, priority = 5)]
+    StringLit(String),
+    // Decimal literals allow a fractional part, a scientific-notation
+    // exponent, and `_` digit separators (only ever between two digits, so
+    // `1_000`, `1.5e-3`, and `2E10` all lex as one token, but a leading,
+    // trailing, or doubled `_` doesn't). `0x`/`0b` literals get their own
+    // patterns since their digit alphabets differ; all three share
+    // `normalize_num_lit`, which just strips the separators, since the
+    // regexes already guarantee everything else about the shape.
+    #[regex(r#"[0-9](_?[0-9])*(\.[0-9](_?[0-9])*)?([eE][+-]?[0-9](_?[0-9])*)?"#, callback = normalize_num_lit, priority = 2)]
+    #[regex(r#"0[xX][0-9a-fA-F](_?[0-9a-fA-F])*"#, callback = normalize_num_lit, priority = 4)]
+    #[regex(r#"0[bB][01](_?[01])*"#, callback = normalize_num_lit, priority = 4)]
+    NumLit(String),
+
+    // Catches what the strict patterns above reject: a lone `0x`/`0b`
+    // prefix with no digits, a trailing or doubled `_`, an exponent with no
+    // digits, or (as before) more than one decimal point. Each alternative
+    // mirrors one of `NumLit`'s patterns with its digit/underscore
+    // constraints loosened, so it only wins the match on malformed input —
+    // on anything well-formed it ties in length with `NumLit` and loses on
+    // priority instead.
+    #[regex(r#"[0-9]+(\.[0-9]+){2,}"#)]
+    #[regex(r#"[0-9][0-9_]*(\.[0-9_]+)?([eE][+-]?[0-9_]*)?"#)]
+    #[regex(r#"0[xX][0-9a-fA-F_]*"#)]
+    #[regex(r#"0[bB][01_]*"#)]
+    MalformedNumber,
 
     // Keywords.
     #[token("and")]
     And,
+    #[token("break")]
+    Break,
     #[token("class")]
     Class,
     #[token("else")]
@@ -102,6 +175,20 @@ pub enum Token<'src> {
 
     #[token("/*", skip_block_comment)]
     UnterminatedBlockComment,
+
+    // A properly closed string whose body contains an escape `decode_string`
+    // doesn't recognize. The looser `\\.` alternative here matches anything
+    // `StringLit`'s regex would, plus more, so it only wins when the
+    // stricter pattern's match is shorter than the full quoted span.
+    #[regex(r#""([^"\\]|\\.)*""#)]
+    MalformedEscapeSequence,
+
+    // Both string patterns above require a closing quote, so an opening
+    // `"` with no match before EOF falls all the way through to this bare
+    // single-character token; the callback then just consumes the rest of
+    // the source, mirroring `skip_block_comment`'s EOF handling.
+    #[token("\"", scan_unterminated_string)]
+    UnterminatedString,
 }
 
 fn skip_block_comment<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Filter<()> {
@@ -118,7 +205,65 @@ fn skip_block_comment<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> Filter
     }
 }
 
-fn trim_string<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> &'src str {
-    let s = lex.slice();
-    &s[1..(s.len() - 1)]
+/// Decodes the body of a quoted string literal whose escapes are all ones
+/// `StringLit`'s regex already validated (`\n`, `\t`, `\r`, `\"`, `\\`, and
+/// `\u{...}`), so this never needs to fail.
+fn decode_string<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> String {
+    let body = lex.slice();
+    let body = &body[1..(body.len() - 1)];
+
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('"') => out.push('"'),
+            Some('\\') => out.push('\\'),
+            Some('u') => {
+                // The regex guarantees a `{...}` of hex digits follows.
+                let rest = chars.as_str();
+                let end = rest.find('}').expect("regex guarantees a closing brace");
+                let code = u32::from_str_radix(&rest[1..end], 16).expect("regex guarantees hex digits");
+                if let Some(decoded) = char::from_u32(code) {
+                    out.push(decoded);
+                }
+                chars = rest[(end + 1)..].chars();
+            }
+            _ => unreachable!("StringLit's regex only allows recognized escapes"),
+        }
+    }
+
+    out
+}
+
+fn scan_unterminated_string<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) {
+    lex.bump(lex.remainder().len());
+}
+
+/// Strips `_` digit separators from a numeric literal's slice. The regex
+/// that matched already guarantees every underscore sits between two
+/// digits, so this never needs to validate anything itself.
+fn normalize_num_lit<'src>(lex: &mut logos::Lexer<'src, Token<'src>>) -> String {
+    lex.slice().replace('_', "")
+}
+
+/// Distinct lexical failure modes, surfaced by the parser as
+/// `ParserErrorKind::Lexical` so tooling can tell a bad escape from a
+/// stray character instead of seeing a generic syntax error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LexicalError {
+    #[error("unterminated string literal")]
+    UnterminatedString,
+    #[error("unrecognized escape sequence in string literal")]
+    MalformedEscapeSequence,
+    #[error("malformed number literal")]
+    MalformedNumber,
 }
@@ -0,0 +1,566 @@
+//! An optional Hindley-Milner type-checking pass that runs after the
+//! `Resolver` and before `Interpreter::execute`, so that programs like the
+//! `type_mismatch` smoke test (`5 + "asd"`) are rejected before anything
+//! runs, rather than at the point the bad expression is reached.
+//!
+//! This is Algorithm W: every expression gets a `Type`, which is either a
+//! concrete type, a function type, or a unification variable; `unify`
+//! walks a mutable substitution map, binding variables to types (with an
+//! occurs-check) and erroring on concrete mismatches.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::ast::*;
+use crate::value::Type as ValueType;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Type {
+    Con(ValueType),
+    Fun(Vec<Type>, Box<Type>),
+    Var(usize),
+}
+
+/// A type scheme: a type with a set of variables universally quantified
+/// over it (`var x = ...` generalizes any variable not free in the
+/// surrounding environment so each use of `x` gets fresh copies).
+#[derive(Clone, Debug)]
+struct Scheme {
+    vars: Vec<usize>,
+    ty: Type,
+}
+
+#[derive(Default)]
+struct Substitution {
+    bindings: HashMap<usize, Type>,
+    next_var: usize,
+}
+
+impl Substitution {
+    fn fresh(&mut self) -> Type {
+        let id = self.next_var;
+        self.next_var += 1;
+        Type::Var(id)
+    }
+
+    /// Follows the substitution chain until it reaches a concrete type, a
+    /// function type, or an unbound variable.
+    fn resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(id) => match self.bindings.get(id) {
+                Some(bound) => self.resolve(bound),
+                None => ty.clone(),
+            },
+            other => other.clone(),
+        }
+    }
+
+    fn occurs(&self, var: usize, ty: &Type) -> bool {
+        match self.resolve(ty) {
+            Type::Var(id) => id == var,
+            Type::Con(_) => false,
+            Type::Fun(params, ret) => {
+                params.iter().any(|p| self.occurs(var, p)) || self.occurs(var, &ret)
+            }
+        }
+    }
+
+    fn unify(&mut self, a: &Type, b: &Type, span: Range) -> Result<(), TypeError> {
+        let a = self.resolve(a);
+        let b = self.resolve(b);
+
+        match (&a, &b) {
+            (Type::Var(x), Type::Var(y)) if x == y => Ok(()),
+            (Type::Var(x), _) => {
+                if self.occurs(*x, &b) {
+                    return Err(TypeError::InfiniteType { span });
+                }
+                self.bindings.insert(*x, b);
+                Ok(())
+            }
+            (_, Type::Var(y)) => {
+                if self.occurs(*y, &a) {
+                    return Err(TypeError::InfiniteType { span });
+                }
+                self.bindings.insert(*y, a);
+                Ok(())
+            }
+            (Type::Con(l), Type::Con(r)) if l == r => Ok(()),
+            (Type::Fun(lp, lr), Type::Fun(rp, rr)) if lp.len() == rp.len() => {
+                for (l, r) in lp.iter().zip(rp) {
+                    self.unify(l, r, span.clone())?;
+                }
+                self.unify(lr, rr, span)
+            }
+            _ => Err(TypeError::Mismatch {
+                expected: a,
+                found: b,
+                span,
+            }),
+        }
+    }
+
+    fn free_vars(&self, ty: &Type, out: &mut Vec<usize>) {
+        match self.resolve(ty) {
+            Type::Var(id) => {
+                if !out.contains(&id) {
+                    out.push(id);
+                }
+            }
+            Type::Con(_) => {}
+            Type::Fun(params, ret) => {
+                for p in &params {
+                    self.free_vars(&p, out);
+                }
+                self.free_vars(&ret, out);
+            }
+        }
+    }
+}
+
+type Range = std::ops::Range<usize>;
+
+/// A stack of name-keyed scopes mirroring `Resolver`'s scope stack, since
+/// the parser only assigns `Reference.id`s to *read* occurrences, not
+/// declaration sites — there's no id to hang a scheme off of until the
+/// variable is actually used, so lookups have to go by name the same way
+/// `Env` resolves locals at runtime.
+#[derive(Default)]
+struct TypeEnv {
+    scopes: VecDeque<HashMap<String, Scheme>>,
+}
+
+impl TypeEnv {
+    fn begin_scope(&mut self) {
+        self.scopes.push_front(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop_front();
+    }
+
+    fn bind_monomorphic(&mut self, name: &str, ty: Type) {
+        self.bind(
+            name,
+            Scheme {
+                vars: Vec::new(),
+                ty,
+            },
+        );
+    }
+
+    fn bind(&mut self, name: &str, scheme: Scheme) {
+        match self.scopes.front_mut() {
+            Some(scope) => {
+                scope.insert(name.to_string(), scheme);
+            }
+            None => {
+                // No enclosing scope means this is a top-level declaration;
+                // give it a scope of its own so later top-level statements
+                // can still see it.
+                let mut scope = HashMap::new();
+                scope.insert(name.to_string(), scheme);
+                self.scopes.push_back(scope);
+            }
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Scheme> {
+        self.scopes.iter().find_map(|scope| scope.get(name))
+    }
+
+    fn instantiate(&self, subst: &mut Substitution, scheme: &Scheme) -> Type {
+        if scheme.vars.is_empty() {
+            return scheme.ty.clone();
+        }
+
+        let mapping: HashMap<usize, Type> =
+            scheme.vars.iter().map(|&v| (v, subst.fresh())).collect();
+        substitute_vars(&scheme.ty, &mapping)
+    }
+
+    /// Quantifies `ty` over every free variable that isn't also free
+    /// somewhere else in the environment, so a function's own type can be
+    /// instantiated afresh at each call site (let-polymorphism).
+    fn generalize(&self, subst: &Substitution, ty: &Type) -> Scheme {
+        let mut ty_vars = Vec::new();
+        subst.free_vars(ty, &mut ty_vars);
+
+        let mut env_vars = Vec::new();
+        for scope in &self.scopes {
+            for scheme in scope.values() {
+                subst.free_vars(&scheme.ty, &mut env_vars);
+            }
+        }
+
+        let vars = ty_vars
+            .into_iter()
+            .filter(|v| !env_vars.contains(v))
+            .collect();
+
+        Scheme {
+            vars,
+            ty: ty.clone(),
+        }
+    }
+}
+
+fn substitute_vars(ty: &Type, mapping: &HashMap<usize, Type>) -> Type {
+    match ty {
+        Type::Var(id) => mapping.get(id).cloned().unwrap_or_else(|| ty.clone()),
+        Type::Con(c) => Type::Con(*c),
+        Type::Fun(params, ret) => Type::Fun(
+            params.iter().map(|p| substitute_vars(p, mapping)).collect(),
+            Box::new(substitute_vars(ret, mapping)),
+        ),
+    }
+}
+
+pub struct Typechecker {
+    subst: Substitution,
+    env: TypeEnv,
+    /// The enclosing function's return type, so a nested `return` (inside
+    /// an `if`/`while` body) unifies against the right variable. `None` at
+    /// the top level, where a bare `return` is meaningless.
+    return_var: Option<Type>,
+}
+
+impl Typechecker {
+    pub fn new() -> Self {
+        Self {
+            subst: Substitution::default(),
+            env: TypeEnv::default(),
+            return_var: None,
+        }
+    }
+
+    pub fn check(mut self, stmts: &[Stmt]) -> Result<(), Vec<TypeError>> {
+        let mut errors = Vec::new();
+
+        for stmt in stmts {
+            if let Err(err) = self.stmt(stmt) {
+                errors.push(err);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Result<(), TypeError> {
+        match stmt {
+            Stmt::Expr(expr) | Stmt::Print(expr) => {
+                self.expr(expr)?;
+                Ok(())
+            }
+            Stmt::Var { name, initializer } => {
+                let ty = match initializer {
+                    Some(init) => self.expr(init)?,
+                    None => self.subst.fresh(),
+                };
+
+                let scheme = self.env.generalize(&self.subst, &ty);
+                self.env.bind(name, scheme);
+                Ok(())
+            }
+            Stmt::Block(stmts) => {
+                self.env.begin_scope();
+                for s in stmts {
+                    self.stmt(s)?;
+                }
+                self.env.end_scope();
+                Ok(())
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let cond_ty = self.expr(condition)?;
+                self.subst
+                    .unify(&cond_ty, &Type::Con(ValueType::Bool), 0..0)?;
+                self.stmt(then_branch)?;
+                if let Some(els) = else_branch {
+                    self.stmt(els)?;
+                }
+                Ok(())
+            }
+            Stmt::While { condition, body } => {
+                let cond_ty = self.expr(condition)?;
+                self.subst
+                    .unify(&cond_ty, &Type::Con(ValueType::Bool), 0..0)?;
+                self.stmt(body)
+            }
+            Stmt::Break => Ok(()),
+            Stmt::Return(None) => {
+                if let Some(ret) = self.return_var.clone() {
+                    self.subst.unify(&ret, &Type::Con(ValueType::Nil), 0..0)?;
+                }
+                Ok(())
+            }
+            Stmt::Return(Some(expr)) => {
+                let ty = self.expr(expr)?;
+                if let Some(ret) = self.return_var.clone() {
+                    self.subst.unify(&ty, &ret, 0..0)?;
+                }
+                Ok(())
+            }
+            Stmt::Function(fun) => self.fun_decl(fun),
+            Stmt::Class(Class {
+                name,
+                superclass,
+                methods,
+            }) => self.class_decl(name, superclass.as_ref(), methods),
+        }
+    }
+
+    /// Classes aren't modeled structurally yet — an instance is just a
+    /// fresh, unconstrained type variable, same as any other name the
+    /// checker can't pin down. Each method body is still checked against
+    /// fresh parameter/`this` variables the same way a free function is,
+    /// just without feeding the result back into a reusable `Scheme` (there's
+    /// no way to instantiate "the type of method `foo`" independent of which
+    /// class it was looked up on).
+    fn class_decl(
+        &mut self,
+        name: &str,
+        superclass: Option<&Reference>,
+        methods: &[Function],
+    ) -> Result<(), TypeError> {
+        if let Some(superclass) = superclass {
+            if let Some(scheme) = self.env.lookup(&superclass.ident).cloned() {
+                self.env.instantiate(&mut self.subst, &scheme);
+            }
+        }
+
+        self.env.begin_scope();
+        self.env.bind_monomorphic("this", self.subst.fresh());
+
+        for method in methods {
+            let param_vars: Vec<Type> = method.params.iter().map(|_| self.subst.fresh()).collect();
+            let ret_var = self.subst.fresh();
+            let prev_return = self.return_var.replace(ret_var);
+
+            self.env.begin_scope();
+            for (param, ty) in method.params.iter().zip(&param_vars) {
+                self.env.bind_monomorphic(param, ty.clone());
+            }
+            let body_result = method.body.iter().try_for_each(|stmt| self.stmt(stmt));
+            self.env.end_scope();
+
+            self.return_var = prev_return;
+            body_result?;
+        }
+
+        self.env.end_scope();
+        self.env.bind_monomorphic(name, self.subst.fresh());
+
+        Ok(())
+    }
+
+    /// Infers a function's type by unifying its body against fresh
+    /// parameter/return variables, then generalizes the result: any
+    /// variable left free in the function's type but not mentioned anywhere
+    /// in the enclosing environment is universally quantified, so each call
+    /// site can instantiate its own copy (e.g. an `identity` function usable
+    /// at both `Num` and `String`).
+    fn fun_decl(&mut self, fun: &Function) -> Result<(), TypeError> {
+        let param_vars: Vec<Type> = fun.params.iter().map(|_| self.subst.fresh()).collect();
+        let ret_var = self.subst.fresh();
+        let fn_ty = Type::Fun(param_vars.clone(), Box::new(ret_var.clone()));
+
+        let prev_return = self.return_var.replace(ret_var);
+
+        // A scope just for the function's own name, so recursive calls
+        // inside the body resolve to `fn_ty` without that monomorphic
+        // binding leaking into the `generalize` call below.
+        self.env.begin_scope();
+        self.env.bind_monomorphic(&fun.name, fn_ty.clone());
+
+        self.env.begin_scope();
+        for (param, ty) in fun.params.iter().zip(&param_vars) {
+            self.env.bind_monomorphic(param, ty.clone());
+        }
+        let body_result = fun.body.iter().try_for_each(|stmt| self.stmt(stmt));
+        self.env.end_scope();
+        self.env.end_scope();
+
+        self.return_var = prev_return;
+        body_result?;
+
+        let scheme = self.env.generalize(&self.subst, &fn_ty);
+        self.env.bind(&fun.name, scheme);
+
+        Ok(())
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Type, TypeError> {
+        Ok(match expr {
+            Expr::Literal(Literal::NumLit(_)) => Type::Con(ValueType::Num),
+            Expr::Literal(Literal::StringLit(_)) => Type::Con(ValueType::String),
+            Expr::Literal(Literal::Bool(_)) => Type::Con(ValueType::Bool),
+            Expr::Literal(Literal::Nil) => Type::Con(ValueType::Nil),
+            Expr::Literal(Literal::Identifier(Reference { ident, .. })) => {
+                match self.env.lookup(ident).cloned() {
+                    Some(scheme) => self.env.instantiate(&mut self.subst, &scheme),
+                    // Unbound names (natives loaded by `stdlib`, or a bug
+                    // the resolver would have already caught) are left as
+                    // an unconstrained variable rather than a hard error.
+                    None => self.subst.fresh(),
+                }
+            }
+            Expr::Grouping(Grouping { expr }) => self.expr(expr)?,
+            Expr::Assign(Assign { value, .. }) => self.expr(value)?,
+            Expr::Unary(Unary { op, right }) => {
+                let right_ty = self.expr(right)?;
+                match op {
+                    UnaryOp::Negation => {
+                        self.subst
+                            .unify(&right_ty, &Type::Con(ValueType::Num), 0..0)?;
+                        Type::Con(ValueType::Num)
+                    }
+                    UnaryOp::Not => Type::Con(ValueType::Bool),
+                }
+            }
+            Expr::Binary(Binary { left, op, right }) => self.binary(left, op.clone(), right)?,
+            Expr::Call(Call { callee, args, paren }) => {
+                let callee_ty = self.expr(callee)?;
+                let arg_tys = args
+                    .iter()
+                    .map(|a| self.expr(a))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let ret = self.subst.fresh();
+                self.subst.unify(
+                    &callee_ty,
+                    &Type::Fun(arg_tys, Box::new(ret.clone())),
+                    paren.clone(),
+                )?;
+                ret
+            }
+            Expr::Get(Get { object, .. }) => {
+                self.expr(object)?;
+                self.subst.fresh()
+            }
+            Expr::Set(Set { object, value, .. }) => {
+                self.expr(object)?;
+                self.expr(value)?
+            }
+            Expr::ArrayLit(ArrayLit { elements }) => {
+                for element in elements {
+                    self.expr(element)?;
+                }
+                self.subst.fresh()
+            }
+            Expr::Index(Index { object, index, .. }) => {
+                self.expr(object)?;
+                self.expr(index)?;
+                self.subst.fresh()
+            }
+            Expr::IndexSet(IndexSet {
+                object,
+                index,
+                value,
+            }) => {
+                self.expr(object)?;
+                self.expr(index)?;
+                self.expr(value)?
+            }
+            Expr::Ternary(Ternary {
+                cond,
+                then_branch,
+                else_branch,
+            }) => {
+                let cond_ty = self.expr(cond)?;
+                self.subst
+                    .unify(&cond_ty, &Type::Con(ValueType::Bool), 0..0)?;
+
+                let then_ty = self.expr(then_branch)?;
+                let else_ty = self.expr(else_branch)?;
+                self.subst.unify(&then_ty, &else_ty, 0..0)?;
+                then_ty
+            }
+        })
+    }
+
+    fn binary(&mut self, left: &Expr, op: BinaryOp, right: &Expr) -> Result<Type, TypeError> {
+        let left_ty = self.expr(left)?;
+        let right_ty = self.expr(right)?;
+
+        use BinaryOp::*;
+        Ok(match op {
+            Add => {
+                // `+` stays overloaded between Num and String; rather than
+                // a real constraint-solving fallback, commit to one of the
+                // two: if the left operand unifies with Num (an unbound
+                // variable does, and is bound to Num), both operands must
+                // be Num; otherwise both must be String.
+                if self
+                    .subst
+                    .unify(&left_ty, &Type::Con(ValueType::Num), 0..0)
+                    .is_ok()
+                {
+                    self.subst
+                        .unify(&right_ty, &Type::Con(ValueType::Num), 0..0)?;
+                    Type::Con(ValueType::Num)
+                } else {
+                    self.subst
+                        .unify(&left_ty, &Type::Con(ValueType::String), 0..0)?;
+                    self.subst
+                        .unify(&right_ty, &Type::Con(ValueType::String), 0..0)?;
+                    Type::Con(ValueType::String)
+                }
+            }
+            Sub | Mul | Div | Mod => {
+                self.subst
+                    .unify(&left_ty, &Type::Con(ValueType::Num), 0..0)?;
+                self.subst
+                    .unify(&right_ty, &Type::Con(ValueType::Num), 0..0)?;
+                Type::Con(ValueType::Num)
+            }
+            Lt | Lte | Gt | Gte => {
+                self.subst
+                    .unify(&left_ty, &Type::Con(ValueType::Num), 0..0)?;
+                self.subst
+                    .unify(&right_ty, &Type::Con(ValueType::Num), 0..0)?;
+                Type::Con(ValueType::Bool)
+            }
+            Eq | NotEq => {
+                self.subst.unify(&left_ty, &right_ty, 0..0)?;
+                Type::Con(ValueType::Bool)
+            }
+            LogicAnd | LogicOr => {
+                self.subst
+                    .unify(&left_ty, &Type::Con(ValueType::Bool), 0..0)?;
+                self.subst
+                    .unify(&right_ty, &Type::Con(ValueType::Bool), 0..0)?;
+                Type::Con(ValueType::Bool)
+            }
+            PipeForward | PipeMap => {
+                // These desugar to a call at runtime; until the checker
+                // models the pipe's implicit argument the result is left
+                // as a fresh, unconstrained variable.
+                self.subst.fresh()
+            }
+        })
+    }
+}
+
+impl Default for Typechecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum TypeError {
+    #[error("expected type {expected:?}, found {found:?}")]
+    Mismatch {
+        expected: Type,
+        found: Type,
+        span: Range,
+    },
+    #[error("infinite type")]
+    InfiniteType { span: Range },
+}
@@ -1,14 +1,128 @@
+use std::cell::RefCell;
 use std::fmt::Display;
+use std::rc::Rc;
 
 use crate::callable::Callable;
+use crate::chunk::VmFunction;
+use crate::class::{Class, Instance};
+
+/// An exact fraction, always stored in lowest terms with a positive
+/// denominator. Produced when integer division doesn't divide evenly
+/// (e.g. `1 / 3`), so exactness survives instead of collapsing to `f64`.
+#[derive(Clone, Copy, Debug)]
+pub struct Rational {
+    pub num: i64,
+    pub den: i64,
+}
+
+impl Rational {
+    /// Normalizes sign onto the numerator and reduces by the gcd. Panics
+    /// on a zero denominator; callers (the interpreter's division) are
+    /// expected to reject that case with a proper `InterpreterError` before
+    /// constructing a `Rational`.
+    pub fn new(num: i64, den: i64) -> Self {
+        assert!(den != 0, "Rational denominator must be non-zero");
+
+        let (num, den) = if den < 0 { (-num, -den) } else { (num, den) };
+        let g = gcd(num.unsigned_abs(), den.unsigned_abs()).max(1);
+        Rational {
+            num: num / g as i64,
+            den: den / g as i64,
+        }
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+
+    /// A rational that happens to be a whole number (denominator reduced
+    /// to 1) should compare/print just like the equivalent `Int`.
+    pub fn as_int(self) -> Option<i64> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl PartialEq for Rational {
+    fn eq(&self, other: &Self) -> bool {
+        self.num == other.num && self.den == other.den
+    }
+}
+
+impl std::ops::Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den + rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl std::ops::Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den - rhs.num * self.den, self.den * rhs.den)
+    }
+}
+
+impl std::ops::Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.num, self.den * rhs.den)
+    }
+}
+
+impl std::ops::Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Rational::new(self.num * rhs.den, self.den * rhs.num)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        // Both denominators are positive, so cross-multiplying preserves
+        // ordering without needing to go through floats.
+        (self.num as i128 * other.den as i128).partial_cmp(&(other.num as i128 * self.den as i128))
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum Value {
     String(String),
+    Int(i64),
+    Rational(Rational),
     Num(f64),
     Bool(bool),
     Nil,
-    Callable(Box<dyn Callable>),
+    /// `Rc`, not `Box`: a callable `Value` is cloned every time it's read
+    /// out of an `Env` (looking up a function by name, passing it as an
+    /// argument, ...), and `Box<dyn Callable>` would deep-clone the
+    /// underlying callable on every one of those instead of just bumping a
+    /// refcount.
+    Callable(Rc<dyn Callable>),
+    /// A `fun`-declared top-level function, compiled to bytecode. The
+    /// tree-walking `Interpreter` never produces this variant — it
+    /// represents the same kind of value `Callable` does, just for the VM
+    /// backend, which has no use for `Callable`'s `&mut Interpreter`-taking
+    /// `call` method (see `vm::Vm::run`'s `OpCode::Call` arm instead).
+    VmFunction(Rc<VmFunction>),
+    Class(Rc<Class>),
+    Instance(Rc<RefCell<Instance>>),
+    /// Array literals and indexing need reference semantics (aliasing an
+    /// array and mutating it through either handle should be visible to
+    /// both), so this follows the same `Rc<RefCell<_>>` shape as `Instance`
+    /// rather than cloning the `Vec` on every copy of the `Value`.
+    Array(Rc<RefCell<Vec<Value>>>),
 }
 
 impl Eq for Value {}
@@ -17,9 +131,22 @@ impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::String(l0), Self::String(r0)) => l0 == r0,
-            (Self::Num(l0), Self::Num(r0)) => l0 == r0,
             (Self::Bool(l0), Self::Bool(r0)) => l0 == r0,
-            (Self::Callable(l0), Self::Callable(r0)) => l0 == r0,
+            (Self::Callable(l0), Self::Callable(r0)) => l0.equals_callable(r0.as_ref()),
+            (Self::VmFunction(l0), Self::VmFunction(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Class(l0), Self::Class(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Instance(l0), Self::Instance(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Array(l0), Self::Array(r0)) => Rc::ptr_eq(l0, r0),
+            (Self::Int(l0), Self::Int(r0)) => l0 == r0,
+            (Self::Rational(l0), Self::Rational(r0)) => l0 == r0,
+            (Self::Num(l0), Self::Num(r0)) => l0 == r0,
+            (Self::Int(i), Self::Rational(r)) | (Self::Rational(r), Self::Int(i)) => {
+                r.as_int() == Some(*i)
+            }
+            (Self::Int(i), Self::Num(n)) | (Self::Num(n), Self::Int(i)) => *i as f64 == *n,
+            (Self::Rational(r), Self::Num(n)) | (Self::Num(n), Self::Rational(r)) => {
+                r.to_f64() == *n
+            }
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
@@ -28,10 +155,15 @@ impl PartialEq for Value {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Type {
     String,
+    Int,
+    Rational,
     Num,
     Bool,
     Nil,
     Callable,
+    Class,
+    Instance,
+    Array,
 }
 
 impl Value {
@@ -42,10 +174,16 @@ impl Value {
     pub fn ty(&self) -> Type {
         match self {
             Value::String(_) => Type::String,
+            Value::Int(_) => Type::Int,
+            Value::Rational(_) => Type::Rational,
             Value::Num(_) => Type::Num,
             Value::Bool(_) => Type::Bool,
             Value::Nil => Type::Nil,
             Value::Callable(_) => Type::Callable,
+            Value::VmFunction(_) => Type::Callable,
+            Value::Class(_) => Type::Class,
+            Value::Instance(_) => Type::Instance,
+            Value::Array(_) => Type::Array,
         }
     }
 }
@@ -53,11 +191,29 @@ impl Value {
 impl Display for Value {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Value::String(s) => write!(f, "\"{}\"", s),
+            Value::String(s) => write!(f, "{}", s),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Rational(r) => match r.as_int() {
+                Some(n) => write!(f, "{}", n),
+                None => write!(f, "{}/{}", r.num, r.den),
+            },
             Value::Num(n) => write!(f, "{}", n),
             Value::Bool(b) => write!(f, "{}", b),
             Value::Nil => write!(f, "nil"),
             Value::Callable(_) => write!(f, "callable"),
+            Value::VmFunction(fun) => write!(f, "<fn {}>", fun.name),
+            Value::Class(class) => write!(f, "{}", class.name),
+            Value::Instance(instance) => write!(f, "{} instance", instance.borrow().class.name),
+            Value::Array(elements) => {
+                write!(f, "[")?;
+                for (ix, element) in elements.borrow().iter().enumerate() {
+                    if ix > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                write!(f, "]")
+            }
         }
     }
 }
@@ -66,10 +222,15 @@ impl Display for Type {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Type::String => write!(f, "string"),
+            Type::Int => write!(f, "int"),
+            Type::Rational => write!(f, "rational"),
             Type::Num => write!(f, "number"),
             Type::Bool => write!(f, "bool"),
             Type::Nil => write!(f, "nil"),
             Type::Callable => write!(f, "callable"),
+            Type::Class => write!(f, "class"),
+            Type::Instance => write!(f, "instance"),
+            Type::Array => write!(f, "array"),
         }
     }
 }
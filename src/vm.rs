@@ -0,0 +1,232 @@
+//! A stack-based bytecode VM, the second of the two interchangeable
+//! execution backends (see [`crate::compiler`] for the other half). It
+//! executes a [`Chunk`] produced by the compiler and must match the
+//! tree-walking `Interpreter`'s semantics exactly: same `Value` returned,
+//! same text written to `InterpreterOutput`.
+
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::callable::Arity;
+use crate::chunk::{Chunk, OpCode, VmFunction};
+use crate::interpreter::{self, ArithOp, InterpreterError, InterpreterOutput};
+use crate::value::{Type, Value};
+
+/// Which [`Chunk`] a [`Frame`] is executing: either the top-level script
+/// chunk `Vm` was constructed with, or a called [`VmFunction`]'s own chunk.
+/// Kept distinct from `Frame` itself so a called function's `Rc<VmFunction>`
+/// can outlive the call that pushed it without borrowing from `Vm`.
+enum ChunkRef<'a> {
+    Top(&'a Chunk),
+    Function(Rc<VmFunction>),
+}
+
+impl<'a> ChunkRef<'a> {
+    fn chunk(&self) -> &Chunk {
+        match self {
+            ChunkRef::Top(chunk) => chunk,
+            ChunkRef::Function(fun) => &fun.chunk,
+        }
+    }
+}
+
+/// One call's worth of execution state: which chunk it's running, where in
+/// that chunk it is, and where its locals start on the shared value stack.
+/// `GetLocal(slot)`/`SetLocal(slot)` are always relative to `base`, so a
+/// function's own slot numbering (assigned fresh per `FunctionCompiler`,
+/// starting at 0) doesn't need to know how deep the call stack is.
+struct Frame<'a> {
+    chunk: ChunkRef<'a>,
+    ip: usize,
+    base: usize,
+}
+
+macro_rules! numeric_binop {
+    ($self:ident, $op:expr) => {{
+        let b = $self.pop();
+        let a = $self.pop();
+        $self.stack.push(interpreter::apply_arith($op, a, b)?);
+    }};
+}
+
+pub struct Vm<'a> {
+    chunk: &'a Chunk,
+    stack: Vec<Value>,
+    globals: Vec<Value>,
+    out: &'a mut InterpreterOutput,
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(chunk: &'a Chunk, global_count: usize, out: &'a mut InterpreterOutput) -> Self {
+        Self {
+            chunk,
+            stack: Vec::new(),
+            globals: vec![Value::Nil; global_count],
+            out,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<Value, InterpreterError> {
+        let mut frames = vec![Frame {
+            chunk: ChunkRef::Top(self.chunk),
+            ip: 0,
+            base: 0,
+        }];
+
+        loop {
+            let (op, base) = {
+                let frame = frames.last().unwrap();
+                let chunk = frame.chunk.chunk();
+                if frame.ip >= chunk.code.len() {
+                    // Falling off the end of a chunk without an explicit
+                    // `Return` only happens to the top-level script (a
+                    // compiled function's chunk always ends with one, even
+                    // if the source `fun` body didn't — see
+                    // `compiler::Compiler::stmt`'s `Stmt::Function` arm).
+                    break;
+                }
+                (chunk.code[frame.ip], frame.base)
+            };
+            frames.last_mut().unwrap().ip += 1;
+
+            match op {
+                OpCode::Constant(ix) => {
+                    let chunk = frames.last().unwrap().chunk.chunk();
+                    self.stack.push(chunk.constants[ix].clone());
+                }
+                OpCode::Add => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    match (a, b) {
+                        (Value::String(a), Value::String(b)) => {
+                            self.stack.push(Value::string(format!("{}{}", a, b)))
+                        }
+                        (a, b) => self.stack.push(interpreter::apply_arith(ArithOp::Add, a, b)?),
+                    }
+                }
+                OpCode::Sub => numeric_binop!(self, ArithOp::Sub),
+                OpCode::Mul => numeric_binop!(self, ArithOp::Mul),
+                OpCode::Div => numeric_binop!(self, ArithOp::Div),
+                OpCode::Mod => numeric_binop!(self, ArithOp::Mod),
+                OpCode::Negate => match self.pop() {
+                    Value::Num(n) => self.stack.push(Value::Num(-n)),
+                    Value::Int(n) => self.stack.push(Value::Int(-n)),
+                    Value::Rational(r) => self
+                        .stack
+                        .push(Value::Rational(crate::value::Rational::new(-r.num, r.den))),
+                    v => {
+                        return Err(InterpreterError::TypeError {
+                            expected: &[Type::Num, Type::Int, Type::Rational],
+                            found: v.ty(),
+                        })
+                    }
+                },
+                OpCode::Not => {
+                    let v = self.pop();
+                    self.stack.push(Value::Bool(!is_truthy(&v)));
+                }
+                OpCode::Equal => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack.push(Value::Bool(a == b));
+                }
+                OpCode::Less => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack
+                        .push(Value::Bool(interpreter::numeric_compare(a, b)? < 0.0));
+                }
+                OpCode::Greater => {
+                    let b = self.pop();
+                    let a = self.pop();
+                    self.stack
+                        .push(Value::Bool(interpreter::numeric_compare(a, b)? > 0.0));
+                }
+                OpCode::Jump(offset) => {
+                    let frame = frames.last_mut().unwrap();
+                    frame.ip = (frame.ip as isize + offset) as usize;
+                }
+                OpCode::JumpIfFalse(offset) => {
+                    if !is_truthy(self.stack.last().unwrap()) {
+                        let frame = frames.last_mut().unwrap();
+                        frame.ip = (frame.ip as isize + offset) as usize;
+                    }
+                }
+                OpCode::Loop(offset) => {
+                    let frame = frames.last_mut().unwrap();
+                    frame.ip = (frame.ip as isize - offset) as usize;
+                }
+                OpCode::Call(argc) => {
+                    let argc = argc as usize;
+                    let callee_ix = self.stack.len() - 1 - argc;
+                    match &self.stack[callee_ix] {
+                        Value::VmFunction(fun) => {
+                            if fun.arity as usize != argc {
+                                return Err(InterpreterError::ArityMismatch {
+                                    expected: Arity::Exact(fun.arity),
+                                    got: argc,
+                                    span: 0..0,
+                                });
+                            }
+                            let fun = Rc::clone(fun);
+                            frames.push(Frame {
+                                chunk: ChunkRef::Function(fun),
+                                ip: 0,
+                                // Args (already on the stack just after the
+                                // callee) become the new frame's locals 0..argc.
+                                base: callee_ix + 1,
+                            });
+                        }
+                        _ => return Err(InterpreterError::NotCallable { span: 0..0 }),
+                    }
+                }
+                OpCode::GetLocal(slot) => self.stack.push(self.stack[base + slot].clone()),
+                OpCode::SetLocal(slot) => {
+                    self.stack[base + slot] = self.stack.last().unwrap().clone()
+                }
+                OpCode::GetGlobal(slot) => self.stack.push(self.globals[slot].clone()),
+                OpCode::SetGlobal(slot) => self.globals[slot] = self.stack.last().unwrap().clone(),
+                OpCode::DefineGlobal(slot) => self.globals[slot] = self.pop(),
+                OpCode::Print => {
+                    let v = self.pop();
+                    writeln!(self.out, "{}", v).unwrap();
+                }
+                OpCode::Pop => {
+                    self.pop();
+                }
+                OpCode::Return => {
+                    let result = self.pop();
+                    let frame = frames.pop().unwrap();
+                    match frames.last() {
+                        // Ending the top-level frame ends the whole run,
+                        // exactly like falling off the end of its chunk did
+                        // before calls existed.
+                        None => return Ok(result),
+                        Some(_) => {
+                            // Drop the callee and its args (stack[base - 1..]),
+                            // leaving just the call's result behind.
+                            self.stack.truncate(frame.base - 1);
+                            self.stack.push(result);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(self.stack.pop().unwrap_or(Value::Nil))
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack
+            .pop()
+            .expect("compiler emitted an unbalanced stack effect")
+    }
+}
+
+fn is_truthy(val: &Value) -> bool {
+    match val {
+        Value::Bool(b) => *b,
+        Value::Nil => false,
+        _ => true,
+    }
+}
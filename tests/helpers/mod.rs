@@ -1,5 +1,6 @@
 use yarrlox::{
     interpreter::{Interpreter, InterpreterError, InterpreterOutput},
+    parser::Parser,
     value::Value,
     EvalErrors, ParserErrorKind, ResolverError,
 };
@@ -30,7 +31,7 @@ impl<'src> RunResults<'src> {
                 .unwrap_err()
                 .unwrap_syn()
                 .into_iter()
-                .map(|err| err.error_kind)
+                .map(|err| err.error_kind().clone())
                 .collect::<Vec<_>>(),
             expected
         );
@@ -45,13 +46,59 @@ impl<'src> RunResults<'src> {
     pub fn assert_runtime_err(self, expected: &[InterpreterError]) {
         assert_eq!(self.v.unwrap_err().unwrap_runtime(), expected);
     }
+
+    #[track_caller]
+    pub fn assert_typecheck_err(self, expected: &[yarrlox::typeck::TypeError]) {
+        assert_eq!(self.v.unwrap_err().unwrap_typecheck(), expected);
+    }
 }
 
 pub fn run(source: &str) -> RunResults<'_> {
     let mut interpreter = Interpreter::new(InterpreterOutput::String(Vec::new()));
+    let mut parser = Parser::new();
     let v = yarrlox::eval(
         source.as_ref(),
         yarrlox::errors::SimpleReporter,
+        &mut parser,
+        &mut interpreter,
+    );
+
+    RunResults {
+        v,
+        output: interpreter.get_output(),
+    }
+}
+
+/// Like [`run`], but runs on the bytecode VM backend instead of the
+/// tree-walking interpreter (see `yarrlox::eval_vm`).
+pub fn run_vm(source: &str) -> RunResults<'_> {
+    let mut out = InterpreterOutput::String(Vec::new());
+    let mut parser = Parser::new();
+    let v = yarrlox::eval_vm(
+        source.as_ref(),
+        yarrlox::errors::SimpleReporter,
+        &mut parser,
+        &mut out,
+    );
+
+    let output = match &mut out {
+        InterpreterOutput::String(bytes) => String::from_utf8(std::mem::take(bytes)).unwrap(),
+        InterpreterOutput::Stdout(_) => String::new(),
+    };
+
+    RunResults { v, output }
+}
+
+/// Like [`run`], but runs the `typeck` pass first (see
+/// `yarrlox::eval_typechecked`), rejecting ill-typed programs before the
+/// interpreter ever executes them.
+pub fn run_typechecked(source: &str) -> RunResults<'_> {
+    let mut interpreter = Interpreter::new(InterpreterOutput::String(Vec::new()));
+    let mut parser = Parser::new();
+    let v = yarrlox::eval_typechecked(
+        source.as_ref(),
+        yarrlox::errors::SimpleReporter,
+        &mut parser,
         &mut interpreter,
     );
 
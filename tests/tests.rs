@@ -1,8 +1,9 @@
 mod helpers;
 
-use helpers::run;
+use helpers::{run, run_typechecked, run_vm};
 
 use yarrlox::{
+    typeck::{self, TypeError},
     value::{Type, Value},
     InterpreterError, ParserErrorKind,
 };
@@ -183,6 +184,55 @@ return fn();
     run(src).assert_v(Value::Num(5.));
 }
 
+#[test]
+fn inheritance_method_lookup() {
+    let src = r#"
+class Animal {
+  speak() {
+    return "...";
+  }
+}
+
+class Dog < Animal {
+  bark() {
+    return "woof";
+  }
+}
+
+var d = Dog();
+print d.speak();
+print d.bark();
+"#;
+
+    run(src).assert_output(
+        r#"
+...
+woof
+"#,
+    );
+}
+
+#[test]
+fn super_call() {
+    let src = r#"
+class Animal {
+  speak() {
+    return "...";
+  }
+}
+
+class Dog < Animal {
+  speak() {
+    return super.speak() + " woof";
+  }
+}
+
+return Dog().speak();
+"#;
+
+    run(src).assert_v(Value::string("... woof"));
+}
+
 #[test]
 fn fib() {
     let fib = r#"
@@ -216,3 +266,162 @@ for (var i = 0; i < 15; i = i + 1) {
     "#,
     );
 }
+
+#[test]
+fn fib_on_vm() {
+    let fib = r#"
+fun fib(n) {
+  if (n <= 1) return n;
+  return fib(n - 2) + fib(n - 1);
+}
+
+for (var i = 0; i < 15; i = i + 1) {
+  print fib(i);
+}
+    "#;
+
+    run_vm(fib).assert_output(
+        r#"
+0
+1
+1
+2
+3
+5
+8
+13
+21
+34
+55
+89
+144
+233
+377
+    "#,
+    );
+}
+
+#[test]
+fn stdlib_natives() {
+    let src = r#"
+print len("hello");
+print str(42);
+print num("3.5");
+print typeof(1);
+print typeof("a");
+print typeof(true);
+"#;
+
+    run(src).assert_output(
+        r#"
+5
+42
+3.5
+int
+string
+bool
+    "#,
+    );
+}
+
+#[test]
+fn numeric_tower_promotion() {
+    let src = r#"
+print 1 / 3;
+print 9223372036854775807 + 1;
+print 1 / 3 + 1.0;
+"#;
+
+    run(src).assert_output(
+        r#"
+1/3
+9223372036854776000
+1.3333333333333333
+    "#,
+    );
+}
+
+#[test]
+fn typecheck_catches_mismatch_before_running() {
+    let src = r#"
+print 1 - "a";
+"#;
+
+    run_typechecked(src).assert_typecheck_err(&[TypeError::Mismatch {
+        expected: typeck::Type::Con(Type::String),
+        found: typeck::Type::Con(Type::Num),
+        span: 0..0,
+    }]);
+}
+
+#[test]
+fn variadic_arity() {
+    let src = r#"
+print min(3, 1, 2);
+print max(3, 1, 2);
+"#;
+
+    run(src).assert_output(
+        r#"
+1
+3
+    "#,
+    );
+}
+
+#[test]
+fn arity_mismatch_is_reported() {
+    let src = "print len();\n";
+
+    run(src).assert_runtime_err(&[InterpreterError::ArityMismatch {
+        expected: yarrlox::Arity::Exact(1),
+        got: 0,
+        span: 10..11,
+    }]);
+}
+
+#[test]
+fn monotonic_timer_never_goes_backwards() {
+    let src = r#"
+var a = monotonic();
+var b = monotonic();
+print b >= a;
+"#;
+
+    run(src).assert_output("true");
+}
+
+#[test]
+fn seeded_random_is_reproducible() {
+    let src = r#"
+seedRandom(42);
+var a = randomInt(0, 1000000);
+var b = randomInt(0, 1000000);
+
+seedRandom(42);
+var c = randomInt(0, 1000000);
+var d = randomInt(0, 1000000);
+
+print a == c;
+print b == d;
+"#;
+
+    run(src).assert_output(
+        r#"
+true
+true
+    "#,
+    );
+}
+
+#[test]
+fn file_io_round_trip() {
+    let src = r#"
+var path = tempFile();
+writeFile(path, "hello");
+appendFile(path, " world");
+print readFile(path);
+"#;
+
+    run(src).assert_output("hello world");
+}